@@ -1,10 +1,10 @@
-use minreq;
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
 use std::fmt::Debug;
 
 use super::entities::patch;
 use super::error::{Error, Result};
+use super::secrets::scrub;
 
 pub struct ListTheDocs {
     base_url: String,
@@ -19,6 +19,10 @@ impl ListTheDocs {
         }
     }
 
+    pub fn url(&self) -> &str {
+        &self.base_url
+    }
+
     pub fn post<B, R>(&self, endpoint_url: &str, body: &B) -> Result<Option<R>>
     where
         B: Serialize + Debug,
@@ -38,28 +42,28 @@ impl ListTheDocs {
             201 => Ok(Some(response.json()?)),
             400 => Err(Error::InputError(format!(
                 "Bad request -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
             401 => Err(Error::InputError("Authorization failed".to_owned())),
             403 => Err(Error::InputError(format!(
                 "Forbidden -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
             404 => Ok(None),
             409 => Err(Error::InputError(format!(
                 "Conflict -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
-            x if x >= 500 && x < 600 => Err(Error::InputError(format!(
+            x if (500..600).contains(&x) => Err(Error::InputError(format!(
                 "The server returned an error -- Response {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
             _ => {
-                println!("{:?}", response.as_str());
+                println!("{:?}", response.as_str().map(scrub));
                 unimplemented!()
             }
         }
@@ -85,12 +89,12 @@ impl ListTheDocs {
             200 => Ok(Some(response.json()?)),
             401 => Err(Error::InputError("Authorization failed".to_owned())),
             404 => Ok(None),
-            x if x >= 500 && x < 600 => Err(Error::InputError(format!(
+            x if (500..600).contains(&x) => Err(Error::InputError(format!(
                 "The server returned an error -- Response {}",
-                response.as_str()?,
+                scrub(response.as_str()?),
             ))),
             _ => {
-                println!("{:?}", response.as_str());
+                println!("{:?}", response.as_str().map(scrub));
                 unimplemented!()
             }
         }
@@ -115,34 +119,35 @@ impl ListTheDocs {
             200 => Ok(response.json()?),
             400 => Err(Error::InputError(format!(
                 "Bad request -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
             401 => Err(Error::InputError("Authorization failed".to_owned())),
             403 => Err(Error::InputError(format!(
                 "Forbidden -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
             404 => Ok(None),
             409 => Err(Error::InputError(format!(
                 "Conflict -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
-            x if x >= 500 && x < 600 => Err(Error::InputError(format!(
+            x if (500..600).contains(&x) => Err(Error::InputError(format!(
                 "The server returned an error -- Response {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 body
             ))),
             _ => {
-                println!("{:?}", response.as_str());
+                println!("{:?}", response.as_str().map(scrub));
                 unimplemented!()
             }
         }
     }
 
-    pub fn remove(&self, endpoint_url: &str, is_404_error: bool) -> Result<()> {
+    /// Returns `false` if the entity to remove was not found and `is_404_error` is not set.
+    pub fn remove(&self, endpoint_url: &str, is_404_error: bool) -> Result<bool> {
         let api_key = self.api_key.as_ref().ok_or_else(|| {
             Error::InputError("API key is required and was not provided".to_owned())
         })?;
@@ -153,23 +158,23 @@ impl ListTheDocs {
             .send()?;
 
         match response.status_code {
-            200 => Ok(()),
+            200 => Ok(true),
             401 => Err(Error::InputError("Authorization failed".to_owned())),
             403 => Err(Error::InputError(format!(
                 "Forbidden -- Response: {}",
-                response.as_str()?,
+                scrub(response.as_str()?),
             ))),
             404 if is_404_error => Err(Error::InputError(format!(
                 "Not found -- Response: {}",
-                response.as_str()?
+                scrub(response.as_str()?)
             ))),
-            404 => Ok(()),
-            x if x >= 500 && x < 600 => Err(Error::InputError(format!(
+            404 => Ok(false),
+            x if (500..600).contains(&x) => Err(Error::InputError(format!(
                 "The server returned an error -- Response {}",
-                response.as_str()?,
+                scrub(response.as_str()?),
             ))),
             _ => {
-                println!("{:?}", response.as_str());
+                println!("{:?}", response.as_str().map(scrub));
                 unimplemented!()
             }
         }
@@ -182,7 +187,7 @@ impl ListTheDocs {
             Error::InputError("API key is required and was not provided".to_owned())
         })?;
 
-        let endpoint_url = &[&self.base_url, "/api/v2/users/", &user_name, "/roles"].concat();
+        let endpoint_url = &[&self.base_url, "/api/v2/users/", user_name, "/roles"].concat();
         let response = minreq::patch(endpoint_url)
             .with_header("Api-Key", api_key)
             .with_json(&roles)?
@@ -192,32 +197,32 @@ impl ListTheDocs {
             200 => Ok(()),
             400 => Err(Error::InputError(format!(
                 "Bad request -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             401 => Err(Error::InputError("Authorization failed".to_owned())),
             403 => Err(Error::InputError(format!(
                 "Forbidden -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             404 => Err(Error::InputError(format!(
                 "Not found -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             409 => Err(Error::InputError(format!(
                 "Conflict -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
-            x if x >= 500 && x < 600 => Err(Error::InputError(format!(
+            x if (500..600).contains(&x) => Err(Error::InputError(format!(
                 "The server returned an error -- Response {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             _ => {
-                println!("{:?}", response.as_str());
+                println!("{:?}", response.as_str().map(scrub));
                 unimplemented!()
             }
         }
@@ -228,7 +233,7 @@ impl ListTheDocs {
             Error::InputError("API key is required and was not provided".to_owned())
         })?;
 
-        let endpoint_url = &[&self.base_url, "/api/v2/users/", &user_name, "/roles"].concat();
+        let endpoint_url = &[&self.base_url, "/api/v2/users/", user_name, "/roles"].concat();
         let response = minreq::delete(endpoint_url)
             .with_header("Api-Key", api_key)
             .with_json(&roles)?
@@ -238,27 +243,27 @@ impl ListTheDocs {
             200 => Ok(()),
             400 => Err(Error::InputError(format!(
                 "Bad request -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             401 => Err(Error::InputError("Authorization failed".to_owned())),
             403 => Err(Error::InputError(format!(
                 "Forbidden -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             404 => Err(Error::InputError(format!(
                 "Not found -- Response: {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
-            x if x >= 500 && x < 600 => Err(Error::InputError(format!(
+            x if (500..600).contains(&x) => Err(Error::InputError(format!(
                 "The server returned an error -- Response {} -- Original request: {:#?}",
-                response.as_str()?,
+                scrub(response.as_str()?),
                 roles
             ))),
             _ => {
-                println!("{:?}", response.as_str());
+                println!("{:?}", response.as_str().map(scrub));
                 unimplemented!()
             }
         }
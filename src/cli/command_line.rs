@@ -2,6 +2,10 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use structopt::StructOpt;
 
+use super::input::InputFormat;
+use super::snapshot::{ConflictStrategy, UrlRewrite};
+use super::timestamp::Timestamp;
+
 #[derive(Debug, StructOpt)]
 pub struct Opt {
     /// The URL to of the ListTheDocs service (e.g., http://localhost:5000)
@@ -12,15 +16,45 @@ pub struct Opt {
     #[structopt(short, long, env = "DOCS_API_KEY", hide_env_values = true)]
     pub api_key: Option<String>,
 
-    /// Path to a json file containing the URL and, optionally, the API key.
+    /// Path to a json, yaml or toml file containing the URL and, optionally, the API key.
     /// If given, other options are not taken into account.
+    /// It can also contain named profiles for other servers, used by `migrate`.
     #[structopt(short, long, parse(from_os_str))]
     pub config: Option<PathBuf>,
 
+    /// The format of the files given with -f: json, yaml or toml.
+    /// By default, it is detected from the file extension, defaulting to json.
+    #[structopt(long, possible_values = &["json", "yaml", "toml"])]
+    pub input_format: Option<InputFormat>,
+
     /// The output is human-readable by default. Use this option for JSON.
     #[structopt(short, long)]
     pub json: bool,
 
+    /// Show API keys in clear text. By default, only a short prefix of each key is shown.
+    /// Keys returned by `user add` are never masked, since that is the only way to read them.
+    #[structopt(long)]
+    pub show_secrets: bool,
+
+    /// When to use colors in the human-readable output: auto, always or never.
+    /// With auto, colors are used if the output is a terminal and NO_COLOR is not set.
+    #[structopt(long, default_value = "auto", possible_values = &["auto", "always", "never"])]
+    pub color: ColorChoice,
+
+    /// Render the output with a Handlebars template. Either the path to a template file
+    /// or one of the built-in templates: markdown, html.
+    #[structopt(long, conflicts_with_all = &["json", "format-string"])]
+    pub template: Option<String>,
+
+    /// Render the output with an inline Handlebars template (e.g., '{{code}}: {{title}}').
+    /// If the result is a list, the template is applied to each element.
+    #[structopt(long, conflicts_with = "json")]
+    pub format_string: Option<String>,
+
+    /// Do not send the output through $PAGER, even if it is a terminal
+    #[structopt(long)]
+    pub no_pager: bool,
+
     #[structopt(subcommand)]
     pub cmd: Command,
 }
@@ -46,6 +80,112 @@ pub enum Command {
         #[structopt(subcommand)]
         role_command: RoleCommand,
     },
+
+    /// Bring the server to the state described by a manifest (in YAML, TOML or JSON).
+    /// The exit code is 1 if any change failed.
+    Apply {
+        /// Path to the manifest listing projects, versions, users and roles
+        #[structopt(parse(from_os_str))]
+        manifest: PathBuf,
+
+        /// Also remove projects, versions, users and roles that are not in the manifest.
+        /// Admin users are never removed.
+        #[structopt(long)]
+        prune: bool,
+    },
+
+    /// Write a snapshot of all projects, versions, users and roles in JSON format
+    Export {
+        /// Leave the API keys of the users out of the snapshot
+        #[structopt(long)]
+        exclude_api_keys: bool,
+    },
+
+    /// Restore projects, versions, users and roles from a snapshot written by `export`
+    Import {
+        /// Path to the snapshot
+        #[structopt(parse(from_os_str))]
+        snapshot: PathBuf,
+
+        /// What to do with projects and versions that already exist with a different content:
+        /// skip them, overwrite them or fail before changing anything
+        #[structopt(
+            long,
+            default_value = "fail",
+            possible_values = &["skip", "overwrite", "fail"]
+        )]
+        on_conflict: ConflictStrategy,
+    },
+
+    /// Copy projects, versions, users and roles from a server to another one
+    Migrate {
+        /// The server to copy from: either the name of a profile in the configuration file
+        /// or the path to a json file containing the URL and the API key
+        #[structopt(long)]
+        from: String,
+
+        /// The server to copy to, in the same form as --from
+        #[structopt(long)]
+        to: String,
+
+        /// Only copy these projects, together with the users holding roles on them
+        #[structopt(long = "project")]
+        projects: Vec<String>,
+
+        /// Rewrite the URLs of the versions, given as OLD_PREFIX=NEW_PREFIX
+        #[structopt(long)]
+        rewrite_url: Option<UrlRewrite>,
+
+        /// What to do with projects and versions that already exist with a different content
+        /// on the destination: skip them, overwrite them or fail before changing anything
+        #[structopt(
+            long,
+            default_value = "fail",
+            possible_values = &["skip", "overwrite", "fail"]
+        )]
+        on_conflict: ConflictStrategy,
+    },
+
+    /// Work with snapshot files, without contacting any server
+    Snapshot {
+        #[structopt(subcommand)]
+        snapshot_command: SnapshotCommand,
+    },
+
+    /// Print the JSON Schema of an input file, for editors to complete and validate it
+    Schema {
+        /// The kind of file: `project` for `project add`, `project-ensure` for `project ensure`,
+        /// `project-update` for `project update` and `project edit`, `version` for `version add`,
+        /// `user` for `user add`, `roles` for `role add` and `role remove`, `config` for the
+        /// configuration file and `manifest` for `apply` and `plan`
+        #[structopt(possible_values = &[
+            "project",
+            "project-ensure",
+            "project-update",
+            "version",
+            "user",
+            "roles",
+            "config",
+            "manifest",
+        ])]
+        kind: SchemaKind,
+    },
+
+    /// Summarize the content of the server: projects, versions, users, API keys and roles
+    Stats,
+
+    /// Show the differences between a manifest and the server, without changing anything.
+    /// The exit code is 2 if there are differences, 0 otherwise.
+    #[structopt(visible_alias = "diff")]
+    Plan {
+        /// Path to the manifest listing projects, versions, users and roles
+        #[structopt(parse(from_os_str))]
+        manifest: PathBuf,
+
+        /// Also show the projects, versions, users and roles that `apply --prune` would remove
+        #[structopt(long)]
+        prune: bool,
+    },
 }
 
 #[derive(Debug, StructOpt)]
@@ -61,21 +201,71 @@ pub enum ProjectCommand {
         /// A link to a logo
         logo: Option<String>,
 
-        /// Path to a json file containing the definition of the project to add.
+        /// A local PNG, JPEG, GIF or SVG image to embed as the logo
+        #[structopt(long, parse(from_os_str), conflicts_with = "logo")]
+        logo_file: Option<PathBuf>,
+
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the definition of
+        /// the project to add, optionally with its versions (as returned by `project get`). It can
+        /// also contain many projects, as an array or one per line.
         /// If given, arguments are not taken into account.
         #[structopt(short, parse(from_os_str))]
         file_path: Option<PathBuf>,
     },
 
+    /// Create a project if it does not exist, or update its description and logo if they differ.
+    /// Projects are matched by title.
+    Ensure {
+        /// The title of the project
+        title: Option<String>,
+
+        /// A short description
+        description: Option<String>,
+
+        /// A link to a logo. If not given, the logo of an existing project is left untouched.
+        logo: Option<String>,
+
+        /// A local PNG, JPEG, GIF or SVG image to embed as the logo
+        #[structopt(long, parse(from_os_str), conflicts_with = "logo")]
+        logo_file: Option<PathBuf>,
+
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the definition of
+        /// the project to ensure.
+        /// If given, arguments are not taken into account.
+        #[structopt(short, parse(from_os_str))]
+        file_path: Option<PathBuf>,
+    },
+
+    /// Show the code that the server will give to a project with the given title
+    CodeFor {
+        /// The title of the project
+        title: String,
+    },
+
     /// Get an existing project
     Get {
         /// The code of the project, as returned upon insertion
         code: String,
     },
 
+    /// Show a project together with its versions, from the latest, the users holding
+    /// each role on it and some statistics
+    Describe {
+        /// The code of the project, as returned upon insertion
+        code: String,
+    },
+
     /// Get the list of all projects
     List,
 
+    /// Find projects by title, code, description or version names, tolerating typos.
+    /// The best matches come first.
+    Search {
+        /// The words to look for. Projects must match all of them.
+        #[structopt(required = true)]
+        terms: Vec<String>,
+    },
+
     /// Update an existing project
     Update {
         /// The code of the project, as returned upon insertion
@@ -85,23 +275,80 @@ pub enum ProjectCommand {
         #[structopt(long = "desc")]
         description: Option<String>,
 
-        /// A link to a logo
+        /// A link to a logo; a warning is printed if it does not resolve to an image
         #[structopt(long)]
         logo: Option<String>,
 
-        /// Path to a json file containing the definition of the project to update.
+        /// A local PNG, JPEG, GIF or SVG image to embed as the logo
+        #[structopt(long, parse(from_os_str), conflicts_with = "logo")]
+        logo_file: Option<PathBuf>,
+
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the definition of
+        /// the project to update.
         /// If given, arguments are not taken into account.
         #[structopt(short, parse(from_os_str))]
         file_path: Option<PathBuf>,
     },
 
+    /// Create a copy of a project with a different title, including all its versions.
+    /// Titles cannot be changed, so this is also the way to fix a title.
+    Clone {
+        /// The code of the project to copy
+        code: String,
+
+        /// The title of the new project
+        #[structopt(long)]
+        title: String,
+
+        /// Also give users the same roles on the new project
+        #[structopt(long)]
+        with_roles: bool,
+
+        /// Remove the original project once it has been copied
+        #[structopt(long)]
+        remove_original: bool,
+    },
+
+    /// Change the title of a project, which also changes its code. Same as
+    /// `clone --with-roles --remove-original`.
+    Rename {
+        /// The code of the project, as returned upon insertion
+        code: String,
+
+        /// The new title
+        title: String,
+    },
+
+    /// Edit the description and the logo of a project in $VISUAL or $EDITOR
+    Edit {
+        /// The code of the project, as returned upon insertion
+        code: String,
+    },
+
+    /// Work with the logos of the projects
+    Logos {
+        #[structopt(subcommand)]
+        logos_command: LogosCommand,
+    },
+
     /// Remove a project
     Remove {
         /// The code of the project, as returned upon insertion
         code: String,
+
+        /// Fail if the project does not exist, instead of reporting that nothing changed
+        #[structopt(long)]
+        fail_if_missing: bool,
     },
 }
 
+#[derive(Debug, StructOpt)]
+pub enum LogosCommand {
+    /// Report the projects whose logo does not resolve to an image.
+    /// The exit code is 1 if any logo is broken.
+    Check,
+}
+
 #[derive(Debug, StructOpt)]
 pub enum VersionCommand {
     Add {
@@ -114,10 +361,20 @@ pub enum VersionCommand {
         /// The URL to the documentation files
         url: Option<String>,
 
-        /// Path to a json file containing the version and URL to add.
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the version and
+        /// URL to add. It can also contain many versions, as an array or one per line.
         /// If given, arguments are not taken into account.
         #[structopt(short, parse(from_os_str))]
         file_path: Option<PathBuf>,
+
+        /// What to do if the version already exists: fail, keep it as it is (skip)
+        /// or change its URL (update)
+        #[structopt(
+            long,
+            default_value = "fail",
+            possible_values = &["fail", "skip", "update"]
+        )]
+        on_conflict: ConflictStrategy,
     },
 
     Update {
@@ -135,7 +392,7 @@ pub enum VersionCommand {
         /// The code of the project, as returned upon insertion
         code: String,
 
-        /// The version to remove (e.g., 1.0.0)
+        /// The version to remove (e.g., 1.0.0). Fails if the version does not exist
         version: String,
     },
 }
@@ -149,7 +406,8 @@ pub enum UserCommand {
         /// Whether the use is an admin or not
         is_admin: Option<bool>,
 
-        /// Path to a json file containing the definition of the user to add.
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the definition of
+        /// the user to add. It can also contain many users, as an array or one per line.
         /// If given, arguments are not taken into account.
         #[structopt(short, parse(from_os_str))]
         file_path: Option<PathBuf>,
@@ -161,11 +419,25 @@ pub enum UserCommand {
     },
 
     /// Get the list of all users
-    List,
+    List {
+        /// Only list users created before this date (e.g., 2026-01-01). Users whose creation date
+        /// is in an unknown format are left out.
+        #[structopt(long)]
+        created_before: Option<Timestamp>,
+
+        /// Only list users created after this date (e.g., 2026-01-01). Users whose creation date
+        /// is in an unknown format are left out.
+        #[structopt(long)]
+        created_after: Option<Timestamp>,
+    },
 
     Remove {
         /// The name of the user
         name: String,
+
+        /// Fail if the user does not exist, instead of reporting that nothing changed
+        #[structopt(long)]
+        fail_if_missing: bool,
     },
 }
 
@@ -178,7 +450,8 @@ pub enum RoleCommand {
         /// A list of roles to add
         project_role: Vec<ProjectRole>,
 
-        /// Path to a json file containing the definition of the roles to add.
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the definition of
+        /// the roles to add, as an array or one per line.
         /// If given, arguments are not taken into account.
         #[structopt(short, parse(from_os_str))]
         file_path: Option<PathBuf>,
@@ -191,10 +464,15 @@ pub enum RoleCommand {
         /// A list of roles to remove
         project_role: Vec<ProjectRole>,
 
-        /// Path to a json file containing the definition of the roles to remove.
+        /// Path to a json, yaml or toml file (`-` for standard input) containing the definition of
+        /// the roles to remove, as an array or one per line.
         /// If given, arguments are not taken into account.
         #[structopt(short, parse(from_os_str))]
         file_path: Option<PathBuf>,
+
+        /// Fail if the user does not have one of the roles, instead of ignoring it
+        #[structopt(long)]
+        fail_if_missing: bool,
     },
 
     Get {
@@ -203,6 +481,20 @@ pub enum RoleCommand {
     },
 }
 
+#[derive(Debug, StructOpt)]
+pub enum SnapshotCommand {
+    /// Show what changed between two snapshots written by `export`
+    Diff {
+        /// Path to the older snapshot
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+
+        /// Path to the newer snapshot
+        #[structopt(parse(from_os_str))]
+        new: PathBuf,
+    },
+}
+
 #[derive(Debug)]
 pub struct ProjectRole {
     pub role_name: String,
@@ -222,6 +514,55 @@ impl FromStr for ProjectRole {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Result::Err(format!("Invalid color choice: {}", s)),
+        }
+    }
+}
+
+/// The input files whose JSON Schema can be printed.
+#[derive(Debug, Clone, Copy)]
+pub enum SchemaKind {
+    Project,
+    ProjectEnsure,
+    ProjectUpdate,
+    Version,
+    User,
+    Roles,
+    Config,
+    Manifest,
+}
+
+impl FromStr for SchemaKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "project" => Ok(SchemaKind::Project),
+            "project-ensure" => Ok(SchemaKind::ProjectEnsure),
+            "project-update" => Ok(SchemaKind::ProjectUpdate),
+            "version" => Ok(SchemaKind::Version),
+            "user" => Ok(SchemaKind::User),
+            "roles" => Ok(SchemaKind::Roles),
+            "config" => Ok(SchemaKind::Config),
+            "manifest" => Ok(SchemaKind::Manifest),
+            _ => Result::Err(format!("Invalid schema: {}", s)),
+        }
+    }
+}
+
 pub fn options_from_args() -> Opt {
     Opt::from_args()
 }
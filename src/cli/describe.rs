@@ -0,0 +1,67 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::entities::get;
+use super::versions;
+
+/// Everything about a project: its metadata, its versions and the users holding roles on it.
+#[derive(Debug, Serialize)]
+pub struct ProjectDescription {
+    pub title: String,
+    pub code: String,
+    pub description: String,
+    pub logo: Option<String>,
+    /// Sorted from the latest to the oldest
+    pub versions: Vec<get::Version>,
+    /// The names of the users holding each role on the project
+    pub roles: BTreeMap<String, Vec<String>>,
+    pub stats: ProjectStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    pub versions: usize,
+    pub latest_version: Option<String>,
+    /// Users holding at least one role on the project
+    pub users: usize,
+}
+
+pub fn describe(project: get::Project, users: &[get::User]) -> ProjectDescription {
+    let code = project.code;
+    let mut project_versions = project.versions;
+    project_versions.sort_by(|a, b| versions::compare(&b.name, &a.name));
+
+    let mut roles: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut users_with_roles = 0;
+    for user in users {
+        let user_roles: Vec<_> = user
+            .roles
+            .iter()
+            .filter(|role| role.project_code == code)
+            .collect();
+        if !user_roles.is_empty() {
+            users_with_roles += 1;
+        }
+        for role in user_roles {
+            roles
+                .entry(role.role_name.clone())
+                .or_default()
+                .push(user.name.clone());
+        }
+    }
+
+    let stats = ProjectStats {
+        versions: project_versions.len(),
+        latest_version: project_versions.first().map(|version| version.name.clone()),
+        users: users_with_roles,
+    };
+    ProjectDescription {
+        title: project.title,
+        code,
+        description: project.description,
+        logo: project.logo,
+        versions: project_versions,
+        roles,
+        stats,
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::error::{Error, Result};
+
+/// How many random names to try before giving up on creating a temporary file.
+const ATTEMPTS: usize = 16;
+
+/// Write `text` to a new temporary file with the given extension and open it in the editor
+/// of the user, taken from `$VISUAL` or `$EDITOR` and defaulting to `vi`.
+///
+/// The path of the edited file is returned, so that the caller can read it back and remove it.
+/// If the file cannot be edited, it is removed before returning the error.
+pub fn edit(extension: &str, text: &str) -> Result<PathBuf> {
+    let (path, file) = create_temporary_file(extension)
+        .map_err(|e| Error::InputError(format!("Cannot create temporary file: {}", e)))?;
+    match run_editor(&path, file, text) {
+        Ok(()) => Ok(path),
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
+
+fn run_editor(path: &Path, mut file: File, text: &str) -> Result<()> {
+    file.write_all(text.as_bytes())
+        .map_err(|e| Error::InputError(format!("Cannot write temporary file: {}", e)))?;
+    drop(file);
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let mut editor_words = editor.split_whitespace();
+    let program = editor_words
+        .next()
+        .ok_or_else(|| Error::InputError("The editor is empty".to_owned()))?;
+    let status = process::Command::new(program)
+        .args(editor_words)
+        .arg(path)
+        .status()
+        .map_err(|e| Error::InputError(format!("Cannot start editor '{}': {}", editor, e)))?;
+    if !status.success() {
+        return Err(Error::InputError(format!(
+            "The editor exited with {}, nothing has been changed",
+            status
+        )));
+    }
+    Ok(())
+}
+
+/// Create a file with a random name in the temporary directory, readable only by the user.
+///
+/// The file must not exist already, so that a file or a symbolic link planted by someone else
+/// is never written to.
+fn create_temporary_file(extension: &str) -> io::Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    for _ in 0..ATTEMPTS {
+        let path = env::temp_dir().join(format!("listthedocs-{:016x}.{}", random(), extension));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "no unused file name found",
+    ))
+}
+
+/// A random number, from the randomly seeded hasher of the standard library.
+fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u128(now.as_nanos());
+    hasher.write_u32(process::id());
+    hasher.finish()
+}
@@ -1,63 +1,68 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub mod get {
     use super::*;
+    use crate::cli::timestamp::Timestamp;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     pub struct Version {
-        name: String,
-        url: String,
+        pub name: String,
+        pub url: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     pub struct Project {
-        title: String,
-        code: String,
-        description: String,
-        logo: Option<String>,
-        versions: Vec<Version>,
+        pub title: String,
+        pub code: String,
+        pub description: String,
+        pub logo: Option<String>,
+        pub versions: Vec<Version>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     pub struct ApiKey {
-        created_at: String,
-        is_valid: bool,
-        key: String,
+        pub created_at: Timestamp,
+        pub is_valid: bool,
+        pub key: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     pub struct Role {
-        role_name: String,
-        project_code: String,
+        pub role_name: String,
+        pub project_code: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     pub struct User {
-        name: String,
-        is_admin: bool,
-        created_at: String,
-        api_keys: Vec<ApiKey>,
-        roles: Vec<Role>,
+        pub name: String,
+        pub is_admin: bool,
+        pub created_at: Timestamp,
+        pub api_keys: Vec<ApiKey>,
+        pub roles: Vec<Role>,
     }
 }
 
 pub mod post {
     use super::*;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
     pub struct Project {
         pub title: String,
         pub description: String,
         pub logo: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
     pub struct Version {
         pub name: String,
         pub url: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
     pub struct User {
         pub name: String,
         pub is_admin: Option<bool>,
@@ -67,18 +72,20 @@ pub mod post {
 pub mod patch {
     use super::*;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
     pub struct Project {
         pub description: Option<String>,
         pub logo: Option<String>,
     }
 
-    #[derive(Debug, Serialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize)]
     pub struct Version {
         pub url: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
     pub struct ProjectRole {
         pub role_name: String,
         pub project_code: String,
@@ -0,0 +1,71 @@
+/// Similarity above which a name is considered a likely misspelling of another one.
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Find the candidate that `name` is most likely a misspelling of, if any.
+pub fn closest<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    similar(name, candidates).into_iter().next()
+}
+
+/// Find the candidates that `name` may be a misspelling of, the most similar first.
+pub fn similar<'a, I>(name: &str, candidates: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut similar: Vec<_> = candidates
+        .into_iter()
+        .map(|candidate| (strsim::jaro_winkler(name, candidate), candidate))
+        .filter(|(similarity, _)| *similarity > SIMILARITY_THRESHOLD)
+        .collect();
+    similar.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    similar
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// How well `term` matches `text`, ignoring case: 1 if `text` contains `term`, otherwise the
+/// similarity with the closest word of `text` if it looks like a misspelling, 0 if no word does.
+pub fn match_score(term: &str, text: &str) -> f64 {
+    let term = term.to_lowercase();
+    let text = text.to_lowercase();
+    if text.contains(&term) {
+        return 1.0;
+    }
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|word| strsim::jaro_winkler(&term, word))
+        .filter(|similarity| *similarity > SIMILARITY_THRESHOLD)
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substrings_match_exactly() {
+        assert_eq!(match_score("Tool", "Documentation tooling"), 1.0);
+        assert_eq!(match_score("ing", "Documentation tooling"), 1.0);
+    }
+
+    #[test]
+    fn misspelled_words_match_partially() {
+        let score = match_score("documantation", "The documentation tooling");
+        assert!(score > SIMILARITY_THRESHOLD && score < 1.0);
+        assert_eq!(match_score("kubernetes", "The documentation tooling"), 0.0);
+        assert_eq!(match_score("anything", ""), 0.0);
+    }
+
+    #[test]
+    fn closest_candidate_first() {
+        let candidates = ["description", "logo", "title"];
+        assert_eq!(closest("descripton", candidates), Some("description"));
+        assert_eq!(closest("versions", candidates), None);
+        assert_eq!(
+            similar("titel", ["title", "titles", "code"]),
+            vec!["title", "titles"]
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::error::{Error, Result};
+use super::fuzzy;
+
+/// The format of an input file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(InputFormat::Json),
+            "yaml" | "yml" => Ok(InputFormat::Yaml),
+            "toml" => Ok(InputFormat::Toml),
+            _ => std::result::Result::Err(format!("Invalid input format: {}", s)),
+        }
+    }
+}
+
+impl InputFormat {
+    /// Detect the format from the extension of the file, defaulting to JSON
+    /// (e.g., for the standard input).
+    fn of(path: &Path) -> InputFormat {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| InputFormat::from_str(extension).ok())
+            .unwrap_or(InputFormat::Json)
+    }
+}
+
+/// Read a single object from a file, or from the standard input if the path is `-`.
+///
+/// If `format` is not given, it is detected from the extension of the file.
+pub fn from_file<P, R>(path: P, format: Option<InputFormat>) -> Result<R>
+where
+    P: AsRef<Path>,
+    R: DeserializeOwned,
+{
+    let path = path.as_ref();
+    parse(path, &read_input(path)?, format)
+}
+
+/// Like `from_file`, but expanding environment variables in the strings of the object
+/// (see `expand_variables`). This is meant for the files given with `-f`, and not for
+/// files holding data, such as snapshots.
+pub fn from_file_expanded<P, R>(path: P, format: Option<InputFormat>) -> Result<R>
+where
+    P: AsRef<Path>,
+    R: DeserializeOwned,
+{
+    let path = path.as_ref();
+    let content = read_input(path)?;
+    // The content is parsed into the final type first, so that errors point to the right line
+    parse::<R>(path, &content, format)?;
+    let value = parse(path, &content, format)?;
+    from_expanded_value(path, value)
+}
+
+/// Parse the content of a file.
+pub fn parse<R>(path: &Path, content: &str, format: Option<InputFormat>) -> Result<R>
+where
+    R: DeserializeOwned,
+{
+    match format.unwrap_or_else(|| InputFormat::of(path)) {
+        InputFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        InputFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        InputFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| invalid_content(path, e))
+}
+
+/// Read many items from a file, or from the standard input if the path is `-`.
+///
+/// A JSON file contains an array, a single object or a stream of objects (such as
+/// newline-delimited JSON). A YAML file contains a sequence, a single item or many documents.
+/// As TOML has no top-level arrays, a TOML file contains either a single item or a single
+/// array of tables (e.g., `[[users]]`).
+///
+/// Environment variables are expanded in the strings of the items, as in `from_file_expanded`.
+pub fn items_from_file_expanded<P, R>(path: P, format: Option<InputFormat>) -> Result<Vec<R>>
+where
+    P: AsRef<Path>,
+    R: DeserializeOwned,
+{
+    let path = path.as_ref();
+    let content = read_input(path)?;
+    // The content is parsed into the final type first, so that errors point to the right line
+    parse_items::<R>(path, &content, format)?;
+    parse_items(path, &content, format)?
+        .into_iter()
+        .map(|value| from_expanded_value(path, value))
+        .collect()
+}
+
+fn parse_items<R>(path: &Path, content: &str, format: Option<InputFormat>) -> Result<Vec<R>>
+where
+    R: DeserializeOwned,
+{
+    match format.unwrap_or_else(|| InputFormat::of(path)) {
+        InputFormat::Json => json_items(content),
+        InputFormat::Yaml => yaml_items(content),
+        InputFormat::Toml => toml_items(content),
+    }
+    .map_err(|e| invalid_content(path, e))
+}
+
+fn json_items<R>(content: &str) -> std::result::Result<Vec<R>, String>
+where
+    R: DeserializeOwned,
+{
+    if content.trim_start().starts_with('[') {
+        serde_json::from_str(content)
+    } else {
+        serde_json::Deserializer::from_str(content)
+            .into_iter()
+            .collect()
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn yaml_items<R>(content: &str) -> std::result::Result<Vec<R>, String>
+where
+    R: DeserializeOwned,
+{
+    // The content is parsed again into the final type, so that errors point to the right line
+    match serde_yaml::from_str::<serde_yaml::Value>(content) {
+        Ok(serde_yaml::Value::Sequence(_)) => serde_yaml::from_str(content),
+        Ok(_) => serde_yaml::from_str(content).map(|item| vec![item]),
+        Err(_) => serde_yaml::Deserializer::from_str(content)
+            .map(R::deserialize)
+            .collect(),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn toml_items<R>(content: &str) -> std::result::Result<Vec<R>, String>
+where
+    R: DeserializeOwned,
+{
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut values = table.values();
+    match (values.next(), values.next()) {
+        (Some(toml::Value::Array(items)), None) => items
+            .iter()
+            .map(|item| item.clone().try_into().map_err(|e| e.to_string()))
+            .collect(),
+        _ => toml::from_str(content)
+            .map(|item| vec![item])
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Read the whole content of a file, or of the standard input if the path is `-`.
+fn read_input(path: &Path) -> Result<String> {
+    let content = if path == Path::new("-") {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(path)
+    };
+    content.map_err(|e| Error::InputError(format!("Cannot open file: {}", e)))
+}
+
+/// Expand the environment variables in all the strings of a parsed object, then convert it
+/// to its final type. Expanding after parsing ensures that values cannot change the structure
+/// of the file.
+fn from_expanded_value<R>(path: &Path, value: Value) -> Result<R>
+where
+    R: DeserializeOwned,
+{
+    let value = expand_value(value, &|name| env::var(name).ok())
+        .map_err(|e| Error::InputError(format!("{} in '{}'", e, path.display())))?;
+    serde_json::from_value(value).map_err(|e| invalid_content(path, e.to_string()))
+}
+
+fn expand_value<F>(value: Value, lookup: &F) -> std::result::Result<Value, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    Ok(match value {
+        Value::String(text) => Value::String(expand_variables(&text, lookup)?),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| expand_value(value, lookup))
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        Value::Object(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| Ok((key, expand_value(value, lookup)?)))
+                .collect::<std::result::Result<_, String>>()?,
+        ),
+        value => value,
+    })
+}
+
+/// Expand `${VAR}` with the value of the variable `VAR`, failing if it is not defined,
+/// and `${VAR:-default}` with `default` if `VAR` is not defined or empty. `$${` is
+/// a literal `${`.
+fn expand_variables<F>(text: &str, lookup: &F) -> std::result::Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after_dollar = &rest[start + 1..];
+        if let Some(after_escape) = after_dollar.strip_prefix("${") {
+            expanded.push_str("${");
+            rest = after_escape;
+            continue;
+        }
+        let variable = match after_dollar.strip_prefix('{') {
+            Some(variable) => variable,
+            None => {
+                expanded.push('$');
+                rest = after_dollar;
+                continue;
+            }
+        };
+
+        let end = variable
+            .find('}')
+            .ok_or_else(|| format!("Unterminated variable {}", &rest[start..]))?;
+        let (name, default) = match variable[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&variable[..end], None),
+        };
+        let is_valid_name =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_valid_name {
+            return Err(format!("Invalid variable name '{}'", name));
+        }
+        let value = lookup(name).filter(|value| !value.is_empty() || default.is_none());
+        match (value, default) {
+            (Some(value), _) => expanded.push_str(&value),
+            (None, Some(default)) => expanded.push_str(default),
+            (None, None) => return Err(format!("Undefined environment variable '{}'", name)),
+        }
+        rest = &variable[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Replace placeholders such as `{code}` with their values.
+pub fn expand_placeholders(text: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(text.to_owned(), |text, (name, value)| {
+        text.replace(&["{", name, "}"].concat(), value)
+    })
+}
+
+fn invalid_content(path: &Path, error: String) -> Error {
+    let error = error.trim_end();
+    let suggestion = suggest_field(error)
+        .map(|field| format!(" (did you mean `{}`?)", field))
+        .unwrap_or_default();
+    Error::InputError(format!(
+        "Invalid content in '{}': {}{}",
+        path.display(),
+        error,
+        suggestion
+    ))
+}
+
+/// Find the likely intended name of an unknown field, from errors such as
+/// "unknown field `descripton`, expected one of `title`, `description`, `logo`".
+fn suggest_field(error: &str) -> Option<&str> {
+    let (_, rest) = error.split_once("unknown field `")?;
+    let (field, rest) = rest.split_once('`')?;
+    let (_, expected) = rest.split_once("expected ")?;
+    let expected = expected.lines().next()?;
+    let candidates = expected.split('`').skip(1).step_by(2);
+    fuzzy::closest(field, candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "VERSION" => Some("1.0".to_owned()),
+            "EMPTY" => Some("".to_owned()),
+            "QUOTE" => Some("a\"b\nc: d".to_owned()),
+            _ => None,
+        }
+    }
+
+    fn expand(text: &str) -> std::result::Result<String, String> {
+        expand_variables(text, &lookup)
+    }
+
+    #[test]
+    fn expand_defined_variables() {
+        assert_eq!(expand("v${VERSION}/docs").unwrap(), "v1.0/docs");
+        assert_eq!(expand("${VERSION}${VERSION}").unwrap(), "1.01.0");
+        assert_eq!(expand("no variables").unwrap(), "no variables");
+    }
+
+    #[test]
+    fn expand_defaults() {
+        assert_eq!(expand("${MISSING:-latest}").unwrap(), "latest");
+        assert_eq!(expand("${VERSION:-latest}").unwrap(), "1.0");
+        assert_eq!(expand("${MISSING:-}").unwrap(), "");
+    }
+
+    #[test]
+    fn empty_variables_are_defined() {
+        assert_eq!(expand("[${EMPTY}]").unwrap(), "[]");
+        assert_eq!(expand("${EMPTY:-default}").unwrap(), "default");
+    }
+
+    #[test]
+    fn undefined_variables_fail() {
+        assert_eq!(
+            expand("${MISSING}").unwrap_err(),
+            "Undefined environment variable 'MISSING'"
+        );
+    }
+
+    #[test]
+    fn unterminated_variables_fail() {
+        assert_eq!(
+            expand("http://${VERSION").unwrap_err(),
+            "Unterminated variable ${VERSION"
+        );
+    }
+
+    #[test]
+    fn invalid_names_fail() {
+        assert_eq!(expand("${}").unwrap_err(), "Invalid variable name ''");
+        assert_eq!(expand("${A-B}").unwrap_err(), "Invalid variable name 'A-B'");
+        assert_eq!(
+            expand("${:-default}").unwrap_err(),
+            "Invalid variable name ''"
+        );
+    }
+
+    #[test]
+    fn escaped_and_lone_dollars_are_kept() {
+        assert_eq!(expand("Costs $${price}").unwrap(), "Costs ${price}");
+        assert_eq!(
+            expand("$${VERSION} is ${VERSION}").unwrap(),
+            "${VERSION} is 1.0"
+        );
+        assert_eq!(expand("Costs $5 or $").unwrap(), "Costs $5 or $");
+    }
+
+    #[test]
+    fn values_cannot_change_the_structure() {
+        let value = json!({"name": "${QUOTE}", "versions": [{"url": "http://x/${VERSION}"}]});
+        assert_eq!(
+            expand_value(value, &lookup).unwrap(),
+            json!({"name": "a\"b\nc: d", "versions": [{"url": "http://x/1.0"}]})
+        );
+    }
+
+    #[test]
+    fn keys_and_other_values_are_not_expanded() {
+        let value = json!({"${VERSION}": true, "count": 1});
+        assert_eq!(expand_value(value.clone(), &lookup).unwrap(), value);
+    }
+}
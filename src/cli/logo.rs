@@ -0,0 +1,141 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use super::error::{Error, Result};
+
+/// Logos are embedded in every response listing projects, so they must stay small.
+const MAX_LOGO_SIZE: usize = 256 * 1024;
+
+/// Seconds to wait for a remote logo.
+const TIMEOUT: u64 = 10;
+
+/// A logo that does not resolve to an image.
+#[derive(Debug, Serialize)]
+pub struct BrokenLogo {
+    pub code: String,
+    pub logo: String,
+    pub problem: String,
+}
+
+/// Read a local PNG, JPEG, GIF or SVG image as a data URI, to be used as a logo.
+pub fn data_uri<P>(path: P) -> Result<String>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let content =
+        fs::read(path).map_err(|e| Error::InputError(format!("Cannot open logo: {}", e)))?;
+    if content.len() > MAX_LOGO_SIZE {
+        return Err(Error::InputError(format!(
+            "The logo is {} KiB, more than the limit of {} KiB",
+            content.len() / 1024,
+            MAX_LOGO_SIZE / 1024
+        )));
+    }
+    let media_type = media_type(&content).ok_or_else(|| {
+        Error::InputError(format!(
+            "'{}' is not a PNG, JPEG, GIF or SVG image",
+            path.display()
+        ))
+    })?;
+    Ok(format!(
+        "data:{};base64,{}",
+        media_type,
+        BASE64.encode(content)
+    ))
+}
+
+/// Detect the type of an image from its content.
+fn media_type(content: &[u8]) -> Option<&'static str> {
+    if content.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if content.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if content.starts_with(b"GIF87a") || content.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if String::from_utf8_lossy(content).contains("<svg") {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+/// Check that a logo is either an image embedded as a data URI or the URL of an image,
+/// returning what is wrong with it otherwise.
+///
+/// Relative links are resolved against `server_url`, as the landing page of the server does.
+pub fn check(logo: &str, server_url: &str) -> std::result::Result<(), String> {
+    if let Some(data) = logo.strip_prefix("data:") {
+        return match data.split_once(',') {
+            Some((header, _)) if header.starts_with("image/") => Ok(()),
+            _ => Err("the data URI is not an image".to_owned()),
+        };
+    }
+
+    let response = minreq::get(resolve(logo, server_url))
+        .with_timeout(TIMEOUT)
+        .send()
+        .map_err(|e| format!("cannot be downloaded: {}", e))?;
+    if !(200..300).contains(&response.status_code) {
+        return Err(format!(
+            "the server answered with status {}",
+            response.status_code
+        ));
+    }
+    match response.headers.get("content-type") {
+        Some(content_type) if content_type.starts_with("image/") => Ok(()),
+        Some(content_type) => Err(format!("it is not an image, but {}", content_type)),
+        None => Err("it has no content type".to_owned()),
+    }
+}
+
+/// Resolve a link to a logo against the URL of the server, unless it is already absolute.
+fn resolve(logo: &str, server_url: &str) -> String {
+    if logo.contains("://") {
+        return logo.to_owned();
+    }
+    let (scheme, rest) = server_url.split_once("://").unwrap_or(("http", server_url));
+    let host = rest.split('/').next().unwrap_or_default();
+    if let Some(path) = logo.strip_prefix("//") {
+        [scheme, "://", path].concat()
+    } else if logo.starts_with('/') {
+        [scheme, "://", host, logo].concat()
+    } else {
+        [server_url.trim_end_matches('/'), "/", logo].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_logos_are_resolved_against_the_server() {
+        let server_url = "http://docs.example.com:5000/landing/";
+        assert_eq!(
+            resolve("mylogo.png", server_url),
+            "http://docs.example.com:5000/landing/mylogo.png"
+        );
+        assert_eq!(
+            resolve("/static/mylogo.png", server_url),
+            "http://docs.example.com:5000/static/mylogo.png"
+        );
+        assert_eq!(
+            resolve("//cdn.example.com/mylogo.png", server_url),
+            "http://cdn.example.com/mylogo.png"
+        );
+        assert_eq!(
+            resolve("https://cdn.example.com/mylogo.png", server_url),
+            "https://cdn.example.com/mylogo.png"
+        );
+    }
+
+    #[test]
+    fn data_uris_must_be_images() {
+        assert!(check("data:image/png;base64,iVBORw0KGgo=", "").is_ok());
+        assert!(check("data:text/plain,hello", "").is_err());
+    }
+}
@@ -0,0 +1,715 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::entities::{get, patch, post};
+use super::error::Result;
+use super::input;
+use super::output::Marker;
+use super::slug;
+
+/// The desired state of a List The Docs instance, as kept under version control.
+///
+/// A missing section is not managed: e.g., if `users` is not given, users are left untouched
+/// even when pruning.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub projects: Option<Vec<Project>>,
+    pub users: Option<Vec<User>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    pub title: String,
+    /// The code of the project, if it is already known. Otherwise, projects are matched by title.
+    pub code: Option<String>,
+    pub description: String,
+    /// If not given, the logo is not managed
+    pub logo: Option<String>,
+    #[serde(default)]
+    pub versions: Vec<post::Version>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct User {
+    pub name: String,
+    pub is_admin: Option<bool>,
+    #[serde(default)]
+    pub roles: Vec<patch::ProjectRole>,
+}
+
+/// A single change needed to bring the server to the state described by a manifest.
+///
+/// Actions are listed in the order they must be executed: creations and updates first,
+/// then removals.
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    CreateProject {
+        project: post::Project,
+        /// The code the project is expected to get, if given in the manifest
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
+        versions: Vec<post::Version>,
+    },
+    UpdateProject {
+        code: String,
+        previous: patch::Project,
+        current: patch::Project,
+    },
+    AddVersion {
+        code: String,
+        version: post::Version,
+    },
+    UpdateVersion {
+        code: String,
+        version: String,
+        previous_url: String,
+        url: String,
+    },
+    CreateUser {
+        user: post::User,
+    },
+    /// The API cannot change whether a user is an admin, so this action always fails: it is
+    /// only planned to report the difference.
+    UpdateUser {
+        name: String,
+        previous_is_admin: bool,
+        is_admin: bool,
+    },
+    AddRoles {
+        user: String,
+        roles: Vec<patch::ProjectRole>,
+    },
+    RemoveRoles {
+        user: String,
+        roles: Vec<patch::ProjectRole>,
+    },
+    RemoveVersion {
+        code: String,
+        version: String,
+    },
+    RemoveUser {
+        name: String,
+    },
+    RemoveProject {
+        code: String,
+    },
+}
+
+impl Action {
+    /// Describe the action as lines of a diff between the server and the manifest.
+    pub fn diff_lines(&self) -> Vec<(Marker, String)> {
+        match self {
+            Action::CreateProject {
+                project, versions, ..
+            } => {
+                let mut lines = vec![(Marker::Add, format!("project '{}'", project.title))];
+                lines.extend(versions.iter().map(|version| {
+                    (
+                        Marker::Add,
+                        format!("    version '{}': {}", version.name, version.url),
+                    )
+                }));
+                lines
+            }
+            Action::UpdateProject {
+                code,
+                previous,
+                current,
+            } => {
+                let mut lines = vec![(Marker::Change, format!("project '{}'", code))];
+                if previous.description != current.description {
+                    lines.push((
+                        Marker::Change,
+                        format!(
+                            "    description: {:?} -> {:?}",
+                            previous.description.as_deref().unwrap_or_default(),
+                            current.description.as_deref().unwrap_or_default()
+                        ),
+                    ));
+                }
+                if previous.logo != current.logo {
+                    lines.push((
+                        Marker::Change,
+                        format!("    logo: {:?} -> {:?}", previous.logo, current.logo),
+                    ));
+                }
+                lines
+            }
+            Action::AddVersion { code, version } => vec![(
+                Marker::Add,
+                format!("version '{}/{}': {}", code, version.name, version.url),
+            )],
+            Action::UpdateVersion {
+                code,
+                version,
+                previous_url,
+                url,
+            } => vec![(
+                Marker::Change,
+                format!(
+                    "version '{}/{}': {} -> {}",
+                    code, version, previous_url, url
+                ),
+            )],
+            Action::CreateUser { user } => {
+                let admin = if user.is_admin == Some(true) {
+                    " (admin)"
+                } else {
+                    ""
+                };
+                vec![(Marker::Add, format!("user '{}'{}", user.name, admin))]
+            }
+            Action::UpdateUser {
+                name,
+                previous_is_admin,
+                is_admin,
+            } => vec![(
+                Marker::Change,
+                format!(
+                    "user '{}': is_admin: {} -> {} (cannot be changed through the API)",
+                    name, previous_is_admin, is_admin
+                ),
+            )],
+            Action::AddRoles { user, roles } => roles
+                .iter()
+                .map(|role| (Marker::Add, role_line(user, role)))
+                .collect(),
+            Action::RemoveRoles { user, roles } => roles
+                .iter()
+                .map(|role| (Marker::Remove, role_line(user, role)))
+                .collect(),
+            Action::RemoveVersion { code, version } => {
+                vec![(Marker::Remove, format!("version '{}/{}'", code, version))]
+            }
+            Action::RemoveUser { name } => vec![(Marker::Remove, format!("user '{}'", name))],
+            Action::RemoveProject { code } => {
+                vec![(Marker::Remove, format!("project '{}'", code))]
+            }
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        match self {
+            Action::CreateProject { .. } => 0,
+            Action::UpdateProject { .. } => 1,
+            Action::AddVersion { .. } => 2,
+            Action::UpdateVersion { .. } => 3,
+            Action::CreateUser { .. } => 4,
+            Action::UpdateUser { .. } => 5,
+            Action::AddRoles { .. } => 6,
+            Action::RemoveRoles { .. } => 7,
+            Action::RemoveVersion { .. } => 8,
+            Action::RemoveUser { .. } => 9,
+            Action::RemoveProject { .. } => 10,
+        }
+    }
+}
+
+/// The result of applying a manifest.
+#[derive(Debug, Serialize)]
+pub struct ApplyReport {
+    pub applied: Vec<Action>,
+    /// Changes that the server rejected, with the reason
+    pub failed: Vec<Failure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Failure {
+    pub action: Action,
+    pub error: String,
+}
+
+/// Load a manifest, in YAML, TOML or JSON format depending on the file extension.
+pub fn load<P>(path: P) -> Result<Manifest>
+where
+    P: AsRef<Path>,
+{
+    input::from_file(path, None)
+}
+
+/// Compute the actions needed to go from the current state of the server to the manifest.
+///
+/// With `prune`, objects that are not in the manifest are removed, except for admin users,
+/// so that the manifest can never lock administrators out of the server.
+pub fn plan(
+    manifest: &Manifest,
+    projects: &[get::Project],
+    users: &[get::User],
+    prune: bool,
+) -> Vec<Action> {
+    let mut actions = vec![];
+
+    if let Some(managed_projects) = &manifest.projects {
+        for managed in managed_projects {
+            match find_project(projects, managed) {
+                Some(existing) => plan_project_update(&mut actions, managed, existing, prune),
+                None => actions.push(Action::CreateProject {
+                    project: post::Project {
+                        title: managed.title.clone(),
+                        description: managed.description.clone(),
+                        logo: managed.logo.clone(),
+                    },
+                    code: managed.code.clone(),
+                    versions: managed.versions.clone(),
+                }),
+            }
+        }
+        if prune {
+            for existing in projects {
+                let is_managed = managed_projects
+                    .iter()
+                    .any(|managed| is_same_project(existing, managed));
+                if !is_managed {
+                    actions.push(Action::RemoveProject {
+                        code: existing.code.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(managed_users) = &manifest.users {
+        for managed in managed_users {
+            let existing_roles = match users.iter().find(|user| user.name == managed.name) {
+                Some(existing) => {
+                    if let Some(is_admin) = managed.is_admin.filter(|a| *a != existing.is_admin) {
+                        actions.push(Action::UpdateUser {
+                            name: existing.name.clone(),
+                            previous_is_admin: existing.is_admin,
+                            is_admin,
+                        });
+                    }
+                    existing.roles.as_slice()
+                }
+                None => {
+                    actions.push(Action::CreateUser {
+                        user: post::User {
+                            name: managed.name.clone(),
+                            is_admin: managed.is_admin,
+                        },
+                    });
+                    &[]
+                }
+            };
+            plan_roles_update(&mut actions, managed, existing_roles, prune);
+        }
+        if prune {
+            for existing in users {
+                let is_managed = managed_users.iter().any(|user| user.name == existing.name);
+                if !is_managed && !existing.is_admin {
+                    actions.push(Action::RemoveUser {
+                        name: existing.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    actions.sort_by_key(Action::order);
+    actions
+}
+
+/// Warn about projects of the manifest that do not exist yet and whose code differs from
+/// the one the server would derive from their title: they would be created again and again.
+pub fn code_warnings(manifest: &Manifest, projects: &[get::Project]) -> Vec<String> {
+    let managed_projects = manifest.projects.iter().flatten();
+    managed_projects
+        .filter(|managed| find_project(projects, managed).is_none())
+        .filter_map(|managed| {
+            let code = managed.code.as_ref()?;
+            let derived_code = slug::code_for(&managed.title);
+            if &derived_code == code {
+                return None;
+            }
+            Some(format!(
+                "Project '{}' will likely be created with code '{}' instead of '{}'",
+                managed.title, derived_code, code
+            ))
+        })
+        .collect()
+}
+
+/// Warn about users of the manifest whose admin flag differs from the one on the server, since
+/// the API cannot change it: the user must be removed and added again.
+pub fn admin_warnings(manifest: &Manifest, users: &[get::User]) -> Vec<String> {
+    let managed_users = manifest.users.iter().flatten();
+    managed_users
+        .filter_map(|managed| {
+            let is_admin = managed.is_admin?;
+            let existing = users.iter().find(|user| user.name == managed.name)?;
+            if existing.is_admin == is_admin {
+                return None;
+            }
+            Some(format!(
+                "User '{}' {} an admin on the server, which cannot be changed through the API",
+                managed.name,
+                if existing.is_admin { "is" } else { "is not" }
+            ))
+        })
+        .collect()
+}
+
+fn plan_project_update(
+    actions: &mut Vec<Action>,
+    managed: &Project,
+    existing: &get::Project,
+    prune: bool,
+) {
+    let logo_changed = managed.logo.is_some() && managed.logo != existing.logo;
+    if managed.description != existing.description || logo_changed {
+        actions.push(Action::UpdateProject {
+            code: existing.code.clone(),
+            previous: patch::Project {
+                description: Some(existing.description.clone()),
+                logo: existing.logo.clone(),
+            },
+            current: patch::Project {
+                description: Some(managed.description.clone()),
+                logo: managed.logo.clone().or_else(|| existing.logo.clone()),
+            },
+        });
+    }
+
+    for version in &managed.versions {
+        match existing.versions.iter().find(|v| v.name == version.name) {
+            Some(existing_version) if existing_version.url != version.url => {
+                actions.push(Action::UpdateVersion {
+                    code: existing.code.clone(),
+                    version: version.name.clone(),
+                    previous_url: existing_version.url.clone(),
+                    url: version.url.clone(),
+                })
+            }
+            Some(_) => {}
+            None => actions.push(Action::AddVersion {
+                code: existing.code.clone(),
+                version: version.clone(),
+            }),
+        }
+    }
+    if prune {
+        for existing_version in &existing.versions {
+            if !managed
+                .versions
+                .iter()
+                .any(|v| v.name == existing_version.name)
+            {
+                actions.push(Action::RemoveVersion {
+                    code: existing.code.clone(),
+                    version: existing_version.name.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn plan_roles_update(
+    actions: &mut Vec<Action>,
+    managed: &User,
+    existing_roles: &[get::Role],
+    prune: bool,
+) {
+    let roles_to_add: Vec<_> = managed
+        .roles
+        .iter()
+        .filter(|role| {
+            !existing_roles
+                .iter()
+                .any(|existing| is_same_role(existing, role))
+        })
+        .cloned()
+        .collect();
+    if !roles_to_add.is_empty() {
+        actions.push(Action::AddRoles {
+            user: managed.name.clone(),
+            roles: roles_to_add,
+        });
+    }
+
+    if prune {
+        let roles_to_remove: Vec<_> = existing_roles
+            .iter()
+            .filter(|existing| {
+                !managed
+                    .roles
+                    .iter()
+                    .any(|role| is_same_role(existing, role))
+            })
+            .map(|existing| patch::ProjectRole {
+                role_name: existing.role_name.clone(),
+                project_code: existing.project_code.clone(),
+            })
+            .collect();
+        if !roles_to_remove.is_empty() {
+            actions.push(Action::RemoveRoles {
+                user: managed.name.clone(),
+                roles: roles_to_remove,
+            });
+        }
+    }
+}
+
+fn role_line(user: &str, role: &patch::ProjectRole) -> String {
+    format!(
+        "role '{}/{}' of user '{}'",
+        role.role_name, role.project_code, user
+    )
+}
+
+fn find_project<'a>(projects: &'a [get::Project], managed: &Project) -> Option<&'a get::Project> {
+    projects
+        .iter()
+        .find(|existing| is_same_project(existing, managed))
+}
+
+fn is_same_project(existing: &get::Project, managed: &Project) -> bool {
+    match &managed.code {
+        Some(code) => &existing.code == code,
+        None => existing.title == managed.title,
+    }
+}
+
+fn is_same_role(existing: &get::Role, role: &patch::ProjectRole) -> bool {
+    existing.role_name == role.role_name && existing.project_code == role.project_code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(value: serde_json::Value) -> Manifest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn server_projects() -> Vec<get::Project> {
+        serde_json::from_value(json!([{
+            "title": "My Project",
+            "code": "my-project",
+            "description": "A project",
+            "logo": "logo.png",
+            "versions": [
+                {"name": "1.0.0", "url": "http://docs/1.0.0"},
+                {"name": "2.0.0", "url": "http://docs/2.0.0"}
+            ]
+        }]))
+        .unwrap()
+    }
+
+    fn server_users() -> Vec<get::User> {
+        serde_json::from_value(json!([
+            {
+                "name": "admin",
+                "is_admin": true,
+                "created_at": "2020-03-05T11:20:30",
+                "api_keys": [],
+                "roles": []
+            },
+            {
+                "name": "alice",
+                "is_admin": false,
+                "created_at": "2020-03-05T11:20:30",
+                "api_keys": [],
+                "roles": [{"role_name": "PROJECT_MANAGER", "project_code": "my-project"}]
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn actions(manifest: &Manifest, prune: bool) -> serde_json::Value {
+        serde_json::to_value(plan(manifest, &server_projects(), &server_users(), prune)).unwrap()
+    }
+
+    #[test]
+    fn create_missing_project_and_user() {
+        let manifest = manifest(json!({
+            "projects": [{
+                "title": "New Project",
+                "description": "New",
+                "versions": [{"name": "1.0", "url": "http://new/1.0"}]
+            }],
+            "users": [{
+                "name": "bob",
+                "roles": [{"role_name": "PROJECT_MANAGER", "project_code": "new-project"}]
+            }]
+        }));
+        assert_eq!(
+            plan(&manifest, &[], &[], false)
+                .iter()
+                .map(|action| action.diff_lines()[0].1.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                "project 'New Project'",
+                "user 'bob'",
+                "role 'PROJECT_MANAGER/new-project' of user 'bob'"
+            ]
+        );
+        match &plan(&manifest, &[], &[], false)[0] {
+            Action::CreateProject { code, versions, .. } => {
+                assert_eq!(code, &None);
+                assert_eq!(versions.len(), 1);
+            }
+            action => panic!("unexpected action {:?}", action),
+        }
+    }
+
+    #[test]
+    fn update_project_and_versions() {
+        let manifest = manifest(json!({
+            "projects": [{
+                "title": "My Project",
+                "description": "An updated project",
+                "logo": "new-logo.png",
+                "versions": [
+                    {"name": "1.0.0", "url": "http://docs/1.0.0"},
+                    {"name": "2.0.0", "url": "http://new-docs/2.0.0"},
+                    {"name": "3.0.0", "url": "http://docs/3.0.0"}
+                ]
+            }]
+        }));
+        assert_eq!(
+            actions(&manifest, false),
+            json!([
+                {
+                    "action": "update_project",
+                    "code": "my-project",
+                    "previous": {"description": "A project", "logo": "logo.png"},
+                    "current": {"description": "An updated project", "logo": "new-logo.png"}
+                },
+                {
+                    "action": "add_version",
+                    "code": "my-project",
+                    "version": {"name": "3.0.0", "url": "http://docs/3.0.0"}
+                },
+                {
+                    "action": "update_version",
+                    "code": "my-project",
+                    "version": "2.0.0",
+                    "previous_url": "http://docs/2.0.0",
+                    "url": "http://new-docs/2.0.0"
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn missing_logo_is_not_managed() {
+        let unchanged = manifest(json!({
+            "projects": [{
+                "code": "my-project",
+                "title": "My Project",
+                "description": "A project",
+                "versions": [
+                    {"name": "1.0.0", "url": "http://docs/1.0.0"},
+                    {"name": "2.0.0", "url": "http://docs/2.0.0"}
+                ]
+            }]
+        }));
+        assert_eq!(actions(&unchanged, true), json!([]));
+
+        let new_description = manifest(json!({
+            "projects": [{"title": "My Project", "description": "An updated project"}]
+        }));
+        assert_eq!(
+            actions(&new_description, false)[0]["current"],
+            json!({"description": "An updated project", "logo": "logo.png"})
+        );
+    }
+
+    #[test]
+    fn prune_removes_what_is_not_in_the_manifest() {
+        let manifest = manifest(json!({
+            "projects": [{
+                "title": "My Project",
+                "description": "A project",
+                "versions": [{"name": "1.0.0", "url": "http://docs/1.0.0"}]
+            }],
+            "users": [{"name": "alice"}]
+        }));
+        assert_eq!(actions(&manifest, false), json!([]));
+        assert_eq!(
+            actions(&manifest, true),
+            json!([
+                {
+                    "action": "remove_roles",
+                    "user": "alice",
+                    "roles": [{"role_name": "PROJECT_MANAGER", "project_code": "my-project"}]
+                },
+                {"action": "remove_version", "code": "my-project", "version": "2.0.0"}
+            ])
+        );
+
+        let other_project = self::manifest(json!({
+            "projects": [{"title": "Other Project", "description": "Other"}]
+        }));
+        let actions = actions(&other_project, true);
+        assert_eq!(actions[0]["action"], "create_project");
+        assert_eq!(
+            actions[1],
+            json!({"action": "remove_project", "code": "my-project"})
+        );
+        assert_eq!(actions.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn prune_never_removes_admins() {
+        let manifest = manifest(json!({"users": []}));
+        assert_eq!(
+            actions(&manifest, true),
+            json!([{"action": "remove_user", "name": "alice"}])
+        );
+    }
+
+    #[test]
+    fn warn_about_codes_the_server_would_not_derive() {
+        let manifest = manifest(json!({
+            "projects": [
+                {"title": "My Project", "code": "my-project", "description": ""},
+                {"title": "New Project", "code": "new-project", "description": ""},
+                {"title": "Other Project", "code": "other", "description": ""}
+            ]
+        }));
+        assert_eq!(
+            code_warnings(&manifest, &server_projects()),
+            vec!["Project 'Other Project' will likely be created with code 'other-project' instead of 'other'"]
+        );
+        match &plan(&manifest, &server_projects(), &[], false)[0] {
+            Action::CreateProject { code, .. } => assert_eq!(code.as_deref(), Some("new-project")),
+            action => panic!("unexpected action {:?}", action),
+        }
+    }
+
+    #[test]
+    fn admin_flag_differences_are_planned_and_warned_about() {
+        let manifest = manifest(json!({
+            "users": [
+                {"name": "admin", "is_admin": false},
+                {"name": "alice", "is_admin": false, "roles": [
+                    {"role_name": "PROJECT_MANAGER", "project_code": "my-project"}
+                ]}
+            ]
+        }));
+        assert_eq!(
+            actions(&manifest, false),
+            json!([{
+                "action": "update_user",
+                "name": "admin",
+                "previous_is_admin": true,
+                "is_admin": false
+            }])
+        );
+        assert_eq!(
+            admin_warnings(&manifest, &server_users()),
+            vec!["User 'admin' is an admin on the server, which cannot be changed through the API"]
+        );
+
+        let unmanaged = self::manifest(json!({"users": [{"name": "admin"}]}));
+        assert_eq!(actions(&unmanaged, false), json!([]));
+        assert!(admin_warnings(&unmanaged, &server_users()).is_empty());
+    }
+}
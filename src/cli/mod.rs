@@ -1,51 +1,163 @@
+use schemars::{schema_for, JsonSchema};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::BufReader;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 mod client;
 mod command_line;
+mod describe;
+mod editor;
 mod entities;
 mod error;
+mod fuzzy;
+mod input;
+mod logo;
+mod manifest;
+mod outcome;
+mod output;
+mod search;
+mod secrets;
+mod slug;
+mod snapshot;
+mod stats;
+mod timestamp;
+mod versions;
 
 pub use command_line::{
-    Command, Opt, ProjectCommand, ProjectRole, RoleCommand, UserCommand, VersionCommand,
+    options_from_args, Command, LogosCommand, Opt, ProjectCommand, ProjectRole, RoleCommand,
+    SchemaKind, SnapshotCommand, UserCommand, VersionCommand,
 };
-
-#[allow(dead_code)]
-pub use command_line::options_from_args;
-// This function is used from main.rs but not from lib.rs, so cargo issues a warning (that we silence).
+pub use output::print;
 
 pub use error::{Error, Result};
 
 use client::ListTheDocs;
 use entities::{get, patch, post};
+use input::{from_file, from_file_expanded, items_from_file_expanded, InputFormat};
+use logo::BrokenLogo;
+use manifest::{Action, ApplyReport, Failure};
+use outcome::{BulkReport, Change, Outcome};
+use output::Output;
+use snapshot::{
+    ConflictStrategy, Difference, MigrationReport, RestoreReport, Snapshot, UrlRewrite,
+};
+use timestamp::Timestamp;
+
+/// Exit code signalling that `plan` or `snapshot diff` found differences.
+const DRIFT_EXIT_CODE: i32 = 2;
+
+/// What a command prints and the exit code of the process.
+pub struct CommandResult {
+    pub output: String,
+    pub exit_code: i32,
+}
+
+pub fn execute_command(opt: Opt) -> Result<CommandResult> {
+    let output = Output::new(opt.json, opt.color, opt.template, opt.format_string)?;
+
+    // These commands do not use the default server, so they do not require it to be configured
+    let cmd = match opt.cmd {
+        Command::Migrate {
+            from,
+            to,
+            projects,
+            rewrite_url,
+            on_conflict,
+        } => {
+            let source = CommandExecutor {
+                list_the_docs: make_profile_client(&from, &opt.config)?,
+                output,
+                show_secrets: opt.show_secrets,
+                input_format: opt.input_format,
+            };
+            let destination = make_profile_client(&to, &opt.config)?;
+            return source.migrate(destination, projects, rewrite_url, on_conflict);
+        }
+        Command::Schema { kind } => return Ok(schema(kind)),
+        Command::Project {
+            project_command: ProjectCommand::CodeFor { title },
+        } => {
+            let code = slug::code_for(&title);
+            let output = if output.json {
+                output.render(&code)?
+            } else {
+                code
+            };
+            return Ok(CommandResult {
+                output,
+                exit_code: 0,
+            });
+        }
+        Command::Snapshot { snapshot_command } => match snapshot_command {
+            SnapshotCommand::Diff { old, new } => return diff_snapshots(&output, old, new),
+        },
+        cmd => cmd,
+    };
 
-pub fn execute_command(opt: Opt) -> Result<String> {
     let list_the_docs = make_client(opt.url.clone(), opt.api_key.clone(), &opt.config)?;
     let executor = CommandExecutor {
         list_the_docs,
-        json_output: opt.json,
+        output,
+        show_secrets: opt.show_secrets,
+        input_format: opt.input_format,
     };
 
-    match opt.cmd {
+    let output = match cmd {
         Command::Project { project_command } => match project_command {
             ProjectCommand::Add {
                 title,
                 description,
                 logo,
+                logo_file,
+                file_path,
+            } => {
+                let logo = logo_from_args(logo, logo_file)?;
+                return executor.add_project(title, description, logo, file_path);
+            }
+            ProjectCommand::Ensure {
+                title,
+                description,
+                logo,
+                logo_file,
                 file_path,
-            } => executor.add_project(title, description, logo, file_path),
+            } => {
+                let logo = logo_from_args(logo, logo_file)?;
+                executor.ensure_project(title, description, logo, file_path)
+            }
+            ProjectCommand::CodeFor { .. } => unreachable!("executed without the server"),
             ProjectCommand::Get { code } => executor.get_project(code),
+            ProjectCommand::Describe { code } => executor.describe_project(code),
             ProjectCommand::List => executor.get_all_projects(),
+            ProjectCommand::Search { terms } => executor.search_projects(terms),
             ProjectCommand::Update {
                 code,
                 description,
                 logo,
+                logo_file,
                 file_path,
-            } => executor.update_project(code, description, logo, file_path),
-            ProjectCommand::Remove { code } => executor.remove_project(code),
+            } => {
+                let logo = logo_from_args(logo, logo_file)?;
+                executor.update_project(code, description, logo, file_path)
+            }
+            ProjectCommand::Logos { logos_command } => match logos_command {
+                LogosCommand::Check => return executor.check_logos(),
+            },
+            ProjectCommand::Clone {
+                code,
+                title,
+                with_roles,
+                remove_original,
+            } => executor.clone_project(code, title, with_roles, remove_original),
+            ProjectCommand::Rename { code, title } => {
+                executor.clone_project(code, title, true, true)
+            }
+            ProjectCommand::Edit { code } => executor.edit_project(code),
+            ProjectCommand::Remove {
+                code,
+                fail_if_missing,
+            } => executor.remove_project(code, fail_if_missing),
         },
         Command::Version { version_command } => match version_command {
             VersionCommand::Add {
@@ -53,7 +165,8 @@ pub fn execute_command(opt: Opt) -> Result<String> {
                 version,
                 url,
                 file_path,
-            } => executor.add_version(code, version, url, file_path),
+                on_conflict,
+            } => return executor.add_version(code, version, url, file_path, on_conflict),
             VersionCommand::Update { code, version, url } => {
                 executor.update_version(code, version, url)
             }
@@ -64,10 +177,16 @@ pub fn execute_command(opt: Opt) -> Result<String> {
                 name,
                 is_admin,
                 file_path,
-            } => executor.add_user(name, is_admin, file_path),
+            } => return executor.add_user(name, is_admin, file_path),
             UserCommand::Get { name } => executor.get_user(name),
-            UserCommand::List => executor.get_all_users(),
-            UserCommand::Remove { name } => executor.remove_user(name),
+            UserCommand::List {
+                created_before,
+                created_after,
+            } => executor.get_all_users(created_before, created_after),
+            UserCommand::Remove {
+                name,
+                fail_if_missing,
+            } => executor.remove_user(name, fail_if_missing),
         },
         Command::Role { role_command } => match role_command {
             RoleCommand::Add {
@@ -79,15 +198,33 @@ pub fn execute_command(opt: Opt) -> Result<String> {
                 user_name,
                 project_role,
                 file_path,
-            } => executor.remove_roles(user_name, project_role, file_path),
+                fail_if_missing,
+            } => executor.remove_roles(user_name, project_role, file_path, fail_if_missing),
             RoleCommand::Get { user_name } => executor.get_roles(user_name),
         },
-    }
+        Command::Apply { manifest, prune } => return executor.apply(manifest, prune),
+        Command::Export { exclude_api_keys } => executor.export(exclude_api_keys),
+        Command::Import {
+            snapshot,
+            on_conflict,
+        } => return executor.import(snapshot, on_conflict),
+        Command::Plan { manifest, prune } => return executor.plan(manifest, prune),
+        Command::Stats => executor.stats(),
+        Command::Migrate { .. } | Command::Snapshot { .. } | Command::Schema { .. } => {
+            unreachable!("commands not using the default server are executed before")
+        }
+    }?;
+    Ok(CommandResult {
+        output,
+        exit_code: 0,
+    })
 }
 
 struct CommandExecutor {
     list_the_docs: ListTheDocs,
-    json_output: bool,
+    output: Output,
+    show_secrets: bool,
+    input_format: Option<InputFormat>,
 }
 
 impl CommandExecutor {
@@ -97,41 +234,219 @@ impl CommandExecutor {
         description: Option<String>,
         logo: Option<String>,
         file_path: Option<PathBuf>,
-    ) -> Result<String> {
-        let project = match file_path {
-            Some(path) => from_file(path)?,
-            None => post::Project {
-                title: title.ok_or_else(|| {
-                    Error::InputError("Missing compulsory 'title' field".to_owned())
-                })?,
-                description: description.ok_or_else(|| {
-                    Error::InputError("Missing compulsory 'description' field".to_owned())
-                })?,
-                logo,
-            },
+    ) -> Result<CommandResult> {
+        // Files may also describe the versions of the project, as returned by `project get`
+        let projects: Vec<manifest::Project> = match file_path {
+            Some(path) => items_from_file_expanded(path, self.input_format)?,
+            None => {
+                let project = project_from_args(title, description, logo, None, None)?;
+                vec![manifest::Project {
+                    title: project.title,
+                    code: None,
+                    description: project.description,
+                    logo: project.logo,
+                    versions: vec![],
+                }]
+            }
+        };
+        // The server derives codes from titles, so surprises are pointed out before adding
+        let existing_projects = self.all_projects()?;
+        for project in &projects {
+            for warning in slug::code_warnings(&project.title, &existing_projects) {
+                eprintln!("Warning: {}", warning);
+            }
+        }
+        self.add_all(
+            projects,
+            |project| project.title.clone(),
+            |project| self.create_project(project, true),
+        )
+    }
+
+    /// With `expand_placeholders`, `{code}` and `{version}` are replaced in the URLs of
+    /// the versions; otherwise, URLs are added as they are.
+    fn create_project(
+        &self,
+        project: manifest::Project,
+        expand_placeholders: bool,
+    ) -> Result<get::Project> {
+        let versions = project.versions;
+        let project = post::Project {
+            title: project.title,
+            description: project.description,
+            logo: project.logo,
         };
-        let added_project: get::Project = self
+        let mut added_project: get::Project = self
             .list_the_docs
             .post("/api/v2/projects", &project)?
             .expect("404 can never be received when adding a project");
-        Ok(to_string(&added_project, self.json_output))
+
+        for version in versions {
+            let version = if expand_placeholders {
+                expand_version(&added_project.code, version)
+            } else {
+                version
+            };
+            let endpoint_url = &["/api/v2/projects/", &added_project.code, "/versions"].concat();
+            let result = self.list_the_docs.post(endpoint_url, &version).and_then(
+                |project: Option<get::Project>| {
+                    project.ok_or_else(|| {
+                        Error::InputError(format!(
+                            "Project with code '{}' not found",
+                            &added_project.code
+                        ))
+                    })
+                },
+            );
+            match result {
+                Ok(project) => added_project = project,
+                Err(e) => {
+                    let failure = format!("Cannot add version '{}'", version.name);
+                    return Err(self.roll_back_project(&added_project.code, &failure, e));
+                }
+            }
+        }
+        Ok(added_project)
+    }
+
+    /// Remove a project that could not be completely set up, so that the command can simply
+    /// be retried. `failure` describes what went wrong; both errors are reported if the project
+    /// cannot be removed.
+    fn roll_back_project(&self, code: &str, failure: &str, error: Error) -> Error {
+        let endpoint_url = &["/api/v2/projects/", code].concat();
+        match self.list_the_docs.remove(endpoint_url, false) {
+            Ok(_) => Error::InputError(format!(
+                "{}, project '{}' has been removed: {}",
+                failure, code, error
+            )),
+            Err(remove_error) => Error::InputError(format!(
+                "{}: {} -- Project '{}' could not be removed either: {}",
+                failure, error, code, remove_error
+            )),
+        }
+    }
+
+    fn ensure_project(
+        &self,
+        title: Option<String>,
+        description: Option<String>,
+        logo: Option<String>,
+        file_path: Option<PathBuf>,
+    ) -> Result<String> {
+        let project = project_from_args(title, description, logo, file_path, self.input_format)?;
+        let existing = self
+            .all_projects()?
+            .into_iter()
+            .find(|existing| existing.title == project.title);
+        let outcome = match existing {
+            None => {
+                let added_project: get::Project = self
+                    .list_the_docs
+                    .post("/api/v2/projects", &project)?
+                    .expect("404 can never be received when adding a project");
+                let code = added_project.code.clone();
+                Outcome::new("project", code, Change::Created, None, Some(added_project))
+            }
+            Some(existing) => {
+                // As in manifests, a missing logo means that the logo is not managed
+                let logo_changed = project.logo.is_some() && project.logo != existing.logo;
+                let current = if project.description != existing.description || logo_changed {
+                    let endpoint_url = &["/api/v2/projects/", &existing.code].concat();
+                    let patch = patch::Project {
+                        description: Some(project.description),
+                        logo: project.logo,
+                    };
+                    self.list_the_docs
+                        .patch(endpoint_url, &patch)?
+                        .ok_or_else(|| {
+                            Error::InputError(format!(
+                                "Project with code '{}' not found",
+                                &existing.code
+                            ))
+                        })?
+                } else {
+                    existing.clone()
+                };
+                Outcome::update("project", existing.code.clone(), existing, current)
+            }
+        };
+        self.output.render(&outcome)
     }
 
     fn get_project(&self, code: String) -> Result<String> {
         let endpoint_url = &["/api/v2/projects/", &code].concat();
         let project: Option<get::Project> = self.list_the_docs.get(endpoint_url, false)?;
         match project {
-            Some(project) => Ok(to_string(&project, self.json_output)),
-            None => Ok(format!("Project with code '{}' not found", code)),
+            Some(project) => self.output.render(&project),
+            None => {
+                let projects = self.all_projects()?;
+                let codes = projects.iter().map(|project| project.code.as_str());
+                let suggestions: Vec<_> = fuzzy::similar(&code, codes)
+                    .into_iter()
+                    .take(3)
+                    .map(|suggestion| ["'", suggestion, "'"].concat())
+                    .collect();
+                if suggestions.is_empty() {
+                    Ok(format!("Project with code '{}' not found", code))
+                } else {
+                    Ok(format!(
+                        "Project with code '{}' not found. Did you mean {}?",
+                        code,
+                        suggestions.join(" or ")
+                    ))
+                }
+            }
         }
     }
 
-    fn get_all_projects(&self) -> Result<String> {
-        let projects: Vec<get::Project> = self
+    fn describe_project(&self, code: String) -> Result<String> {
+        let endpoint_url = &["/api/v2/projects/", &code].concat();
+        let project: get::Project = self
             .list_the_docs
-            .get("/api/v2/projects", false)?
-            .expect("404 can never be received when getting all projects");
-        Ok(to_string(&projects, self.json_output))
+            .get(endpoint_url, false)?
+            .ok_or_else(|| Error::InputError(format!("Project with code '{}' not found", &code)))?;
+        let description = describe::describe(project, &self.all_users()?);
+        self.output.render(&description)
+    }
+
+    fn get_all_projects(&self) -> Result<String> {
+        self.output.render(&self.all_projects()?)
+    }
+
+    fn search_projects(&self, terms: Vec<String>) -> Result<String> {
+        let results = search::search(self.all_projects()?, &terms);
+        self.output.render_text(&results, || {
+            if results.is_empty() {
+                return "No projects found".to_owned();
+            }
+            let highlight = |text: &str| self.output.highlight(text, &terms);
+            let mut lines = vec![];
+            for result in &results {
+                let project = &result.project;
+                lines.push(format!(
+                    "{}: {}",
+                    highlight(&project.code),
+                    highlight(&project.title)
+                ));
+                if result.matches.contains(&"description") {
+                    lines.push(["    ", &highlight(&project.description)].concat());
+                }
+                if result.matches.contains(&"versions") {
+                    let names: Vec<_> = project
+                        .versions
+                        .iter()
+                        .map(|version| version.name.as_str())
+                        .filter(|name| {
+                            terms
+                                .iter()
+                                .any(|term| fuzzy::match_score(term, name) > 0.0)
+                        })
+                        .collect();
+                    lines.push(["    versions: ", &highlight(&names.join(", "))].concat());
+                }
+            }
+            lines.join("\n")
+        })
     }
 
     fn update_project(
@@ -142,14 +457,21 @@ impl CommandExecutor {
         file_path: Option<PathBuf>,
     ) -> Result<String> {
         let project = match file_path {
-            Some(path) => from_file(path)?,
-            None => patch::Project { description, logo },
+            Some(path) => from_file_expanded(path, self.input_format)?,
+            None => {
+                if let Some(logo) = &logo {
+                    if let Err(problem) = logo::check(logo, self.list_the_docs.url()) {
+                        eprintln!("Warning: the logo '{}' may be broken: {}", logo, problem);
+                    }
+                }
+                patch::Project { description, logo }
+            }
         };
 
         let endpoint_url = &["/api/v2/projects/", &code].concat();
-        let project: Option<get::Project> = self.list_the_docs.patch(&endpoint_url, &project)?;
+        let project: Option<get::Project> = self.list_the_docs.patch(endpoint_url, &project)?;
         match project {
-            Some(project) => Ok(to_string(&project, self.json_output)),
+            Some(project) => self.output.render(&project),
             None => Err(Error::InputError(format!(
                 "Project with code '{}' not found",
                 &code
@@ -157,11 +479,208 @@ impl CommandExecutor {
         }
     }
 
-    fn remove_project(&self, code: String) -> Result<String> {
+    /// The copy is removed again if the roles cannot be copied. The original project is only
+    /// removed once the copy is complete.
+    fn clone_project(
+        &self,
+        code: String,
+        title: String,
+        with_roles: bool,
+        remove_original: bool,
+    ) -> Result<String> {
+        // The server would refuse to create a second project with the same code
+        let new_code = slug::code_for(&title);
+        if new_code == code {
+            return Err(Error::InputError(format!(
+                "Title '{}' gives the same code '{}' as the original project, so it cannot be \
+                 used for a copy",
+                title, code
+            )));
+        }
+
+        let endpoint_url = &["/api/v2/projects/", &code].concat();
+        let original: get::Project = self
+            .list_the_docs
+            .get(endpoint_url, false)?
+            .ok_or_else(|| Error::InputError(format!("Project with code '{}' not found", &code)))?;
+
+        // URLs are copied verbatim, even if they contain placeholders
+        let copy = self.create_project(
+            manifest::Project {
+                title,
+                code: None,
+                description: original.description.clone(),
+                logo: original.logo.clone(),
+                versions: original
+                    .versions
+                    .iter()
+                    .map(|version| post::Version {
+                        name: version.name.clone(),
+                        url: version.url.clone(),
+                    })
+                    .collect(),
+            },
+            false,
+        )?;
+
+        let mut roles_by_user = vec![];
+        if with_roles || remove_original {
+            let users = self
+                .all_users()
+                .map_err(|e| self.roll_back_project(&copy.code, "Cannot read the roles", e))?;
+            for user in users {
+                let roles: Vec<_> = user
+                    .roles
+                    .iter()
+                    .filter(|role| role.project_code == original.code)
+                    .map(|role| patch::ProjectRole {
+                        role_name: role.role_name.clone(),
+                        project_code: role.project_code.clone(),
+                    })
+                    .collect();
+                if !roles.is_empty() {
+                    roles_by_user.push((user.name, roles));
+                }
+            }
+        }
+
+        if with_roles {
+            for (user_name, roles) in &roles_by_user {
+                let copied_roles: Vec<_> = roles
+                    .iter()
+                    .map(|role| patch::ProjectRole {
+                        role_name: role.role_name.clone(),
+                        project_code: copy.code.clone(),
+                    })
+                    .collect();
+                if let Err(e) = self.list_the_docs.add_roles(user_name, &copied_roles) {
+                    let failure = format!("Cannot copy the roles of user '{}'", user_name);
+                    return Err(self.roll_back_project(&copy.code, &failure, e));
+                }
+            }
+        }
+
+        let outcome = if remove_original {
+            // Roles on the original project would be left dangling
+            let removal = roles_by_user
+                .iter()
+                .try_for_each(|(user_name, roles)| {
+                    self.list_the_docs.remove_roles(user_name, roles)
+                })
+                .and_then(|_| self.list_the_docs.remove(endpoint_url, true));
+            if let Err(e) = removal {
+                return Err(Error::InputError(format!(
+                    "Project '{}' has been copied to '{}', but it could not be removed: {}",
+                    code, copy.code, e
+                )));
+            }
+            Outcome::new(
+                "project",
+                copy.code.clone(),
+                Change::Updated,
+                Some(original),
+                Some(copy),
+            )
+        } else {
+            Outcome::new(
+                "project",
+                copy.code.clone(),
+                Change::Created,
+                None,
+                Some(copy),
+            )
+        };
+        self.output.render(&outcome)
+    }
+
+    fn edit_project(&self, code: String) -> Result<String> {
+        let endpoint_url = &["/api/v2/projects/", &code].concat();
+        let not_found = || Error::InputError(format!("Project with code '{}' not found", &code));
+        let previous: get::Project = self
+            .list_the_docs
+            .get(endpoint_url, false)?
+            .ok_or_else(not_found)?;
+
+        let editable = patch::Project {
+            description: Some(previous.description.clone()),
+            logo: previous.logo.clone(),
+        };
+        let text = serde_yaml::to_string(&editable)
+            .unwrap_or_else(|e| panic!("serde serialization failed: {}", e));
+        let path = editor::edit("yaml", &text)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| Error::InputError(format!("Cannot open file: {}", e)))?;
+        let edited: patch::Project = input::parse(&path, &content, None).map_err(|e| {
+            Error::InputError(format!(
+                "{}. Nothing has been changed, and your edit is kept in '{}'",
+                e,
+                path.display()
+            ))
+        })?;
+
+        if previous.logo.is_some() && edited.logo.is_none() {
+            eprintln!("The logo cannot be removed, so it is left unchanged");
+        }
+        // Only changed fields are sent
+        let changes = patch::Project {
+            description: edited
+                .description
+                .filter(|description| *description != previous.description),
+            logo: edited
+                .logo
+                .filter(|logo| Some(logo) != previous.logo.as_ref()),
+        };
+        if changes.description.is_none() && changes.logo.is_none() {
+            let _ = fs::remove_file(&path);
+            let outcome = Outcome::update("project", code, previous.clone(), previous);
+            return self.output.render(&outcome);
+        }
+
+        let latest: Option<get::Project> = self.list_the_docs.get(endpoint_url, false)?;
+        if latest.as_ref() != Some(&previous) {
+            return Err(Error::InputError(format!(
+                "Project '{}' was changed by someone else while editing it. \
+                 Your version is kept in '{}'",
+                code,
+                path.display()
+            )));
+        }
+        let current: get::Project = self
+            .list_the_docs
+            .patch(endpoint_url, &changes)?
+            .ok_or_else(not_found)?;
+        let _ = fs::remove_file(&path);
+        let outcome = Outcome::update("project", code, previous, current);
+        self.output.render(&outcome)
+    }
+
+    fn check_logos(&self) -> Result<CommandResult> {
+        let projects = self.all_projects()?;
+        let mut broken = vec![];
+        for project in projects {
+            if let Some(logo) = project.logo {
+                if let Err(problem) = logo::check(&logo, self.list_the_docs.url()) {
+                    broken.push(BrokenLogo {
+                        code: project.code,
+                        logo,
+                        problem,
+                    });
+                }
+            }
+        }
+        let exit_code = if broken.is_empty() { 0 } else { 1 };
+        Ok(CommandResult {
+            output: self.output.render(&broken)?,
+            exit_code,
+        })
+    }
+
+    fn remove_project(&self, code: String, fail_if_missing: bool) -> Result<String> {
         let endpoint_url = &["/api/v2/projects/", &code].concat();
-        self.list_the_docs
-            .remove(&endpoint_url, false)
-            .map(|_| code)
+        let previous: Option<get::Project> = self.list_the_docs.get(endpoint_url, false)?;
+        let removed = self.list_the_docs.remove(endpoint_url, fail_if_missing)?;
+        let outcome = Outcome::removal("project", code, previous, removed);
+        self.output.render(&outcome)
     }
 
     fn add_version(
@@ -170,48 +689,109 @@ impl CommandExecutor {
         version: Option<String>,
         url: Option<String>,
         file_path: Option<PathBuf>,
-    ) -> Result<String> {
-        let version = match file_path {
-            Some(path) => from_file(path)?,
-            None => post::Version {
+        on_conflict: ConflictStrategy,
+    ) -> Result<CommandResult> {
+        let versions: Vec<post::Version> = match file_path {
+            Some(path) => items_from_file_expanded(path, self.input_format)?,
+            None => vec![post::Version {
                 name: version.ok_or_else(|| {
                     Error::InputError("Missing compulsory 'version' field".to_owned())
                 })?,
                 url: url.ok_or_else(|| {
                     Error::InputError("Missing compulsory 'url' field".to_owned())
                 })?,
-            },
+            }],
         };
+        self.add_all(
+            versions,
+            |version| version.name.clone(),
+            |version| self.create_version(&code, version, on_conflict),
+        )
+    }
 
-        let endpoint_url = &["/api/v2/projects/", &code, "/versions"].concat();
-        let project: Option<get::Project> = self.list_the_docs.post(&endpoint_url, &version)?;
-        match project {
-            Some(project) => Ok(to_string(&project, self.json_output)),
-            None => Err(Error::InputError(format!(
-                "Project with code '{}' not found",
-                &code
-            ))),
+    fn create_version(
+        &self,
+        code: &str,
+        version: post::Version,
+        on_conflict: ConflictStrategy,
+    ) -> Result<Outcome<get::Version>> {
+        let version = expand_version(code, version);
+        let id = [code, "/", &version.name].concat();
+
+        // When failing, the conflict is reported by the server
+        if on_conflict != ConflictStrategy::Fail {
+            let endpoint_url = &["/api/v2/projects/", code].concat();
+            let project: Option<get::Project> = self.list_the_docs.get(endpoint_url, false)?;
+            let project = project.ok_or_else(|| {
+                Error::InputError(format!("Project with code '{}' not found", code))
+            })?;
+            if let Some(existing) = find_version(project, &version.name) {
+                if on_conflict == ConflictStrategy::Skip || existing.url == version.url {
+                    let current = Some(existing.clone());
+                    return Ok(Outcome::new(
+                        "version",
+                        id,
+                        Change::Unchanged,
+                        Some(existing),
+                        current,
+                    ));
+                }
+                let project = self.patch_version(code, &version.name, version.url)?;
+                let current = find_version(project, &version.name);
+                return Ok(Outcome::new(
+                    "version",
+                    id,
+                    Change::Updated,
+                    Some(existing),
+                    current,
+                ));
+            }
         }
+
+        let endpoint_url = &["/api/v2/projects/", code, "/versions"].concat();
+        let project: Option<get::Project> = self.list_the_docs.post(endpoint_url, &version)?;
+        let project = project
+            .ok_or_else(|| Error::InputError(format!("Project with code '{}' not found", code)))?;
+        let current = find_version(project, &version.name);
+        Ok(Outcome::new("version", id, Change::Created, None, current))
     }
 
     fn update_version(&self, code: String, version: String, url: String) -> Result<String> {
+        let project = self.patch_version(&code, &version, url)?;
+        self.output.render(&project)
+    }
+
+    fn patch_version(&self, code: &str, version: &str, url: String) -> Result<get::Project> {
         let url = patch::Version { url };
-        let endpoint_url = &["/api/v2/projects/", &code, "/versions/", &version].concat();
-        let project: Option<get::Project> = self.list_the_docs.patch(&endpoint_url, &url)?;
-        match project {
-            Some(project) => Ok(to_string(&project, self.json_output)),
-            None => Err(Error::InputError(format!(
+        let endpoint_url = &["/api/v2/projects/", code, "/versions/", version].concat();
+        let project: Option<get::Project> = self.list_the_docs.patch(endpoint_url, &url)?;
+        project.ok_or_else(|| {
+            Error::InputError(format!(
                 "Project with code '{}' or version '{}' not found",
-                &code, &version
-            ))),
-        }
+                code, version
+            ))
+        })
     }
 
     fn remove_version(&self, code: String, version: String) -> Result<String> {
+        let project_url = &["/api/v2/projects/", &code].concat();
+        let project: Option<get::Project> = self.list_the_docs.get(project_url, false)?;
+        let previous = project.and_then(|project| {
+            project
+                .versions
+                .into_iter()
+                .find(|project_version| project_version.name == version)
+        });
+
         let endpoint_url = &["/api/v2/projects/", &code, "/versions/", &version].concat();
-        self.list_the_docs
-            .remove(&endpoint_url, true)
-            .map(|_| version)
+        let removed = self.list_the_docs.remove(endpoint_url, true)?;
+        let outcome = Outcome::removal(
+            "version",
+            [&code, "/", &version].concat(),
+            previous,
+            removed,
+        );
+        self.output.render(&outcome)
     }
 
     fn add_user(
@@ -219,45 +799,68 @@ impl CommandExecutor {
         name: Option<String>,
         is_admin: Option<bool>,
         file_path: Option<PathBuf>,
-    ) -> Result<String> {
-        let user = match file_path {
-            Some(path) => from_file(path)?,
-            None => post::User {
+    ) -> Result<CommandResult> {
+        let users: Vec<post::User> = match file_path {
+            Some(path) => items_from_file_expanded(path, self.input_format)?,
+            None => vec![post::User {
                 name: name.ok_or_else(|| {
                     Error::InputError("Missing compulsory 'name' field".to_owned())
                 })?,
                 is_admin,
-            },
+            }],
         };
-        let added_user: get::User = self
-            .list_the_docs
-            .post("/api/v2/users", &user)?
-            .expect("404 can never be received when adding a user");
-        Ok(to_string(&added_user, self.json_output))
+        self.add_all(
+            users,
+            |user| user.name.clone(),
+            |user| {
+                let added_user: get::User = self
+                    .list_the_docs
+                    .post("/api/v2/users", &user)?
+                    .expect("404 can never be received when adding a user");
+                Ok(added_user)
+            },
+        )
     }
 
     fn get_user(&self, name: String) -> Result<String> {
         let endpoint_url = &["/api/v2/users/", &name].concat();
-        let user: Option<get::User> = self.list_the_docs.get(&endpoint_url, true)?;
+        let user: Option<get::User> = self.list_the_docs.get(endpoint_url, true)?;
         match user {
-            Some(user) => Ok(to_string(&user, self.json_output)),
+            Some(user) => self.output.render(&self.redact(user)),
             None => Ok(format!("User with name '{}' not found", name)),
         }
     }
 
-    fn get_all_users(&self) -> Result<String> {
-        let users: Vec<get::User> = self
-            .list_the_docs
-            .get("/api/v2/users", true)?
-            .expect("404 can never be received when getting all users");
-        Ok(to_string(&users, self.json_output))
+    fn get_all_users(
+        &self,
+        created_before: Option<Timestamp>,
+        created_after: Option<Timestamp>,
+    ) -> Result<String> {
+        let users: Vec<_> = self
+            .all_users()?
+            .into_iter()
+            .filter(|user| {
+                created_before
+                    .as_ref()
+                    .is_none_or(|before| &user.created_at < before)
+            })
+            .filter(|user| {
+                created_after
+                    .as_ref()
+                    .is_none_or(|after| &user.created_at > after)
+            })
+            .map(|user| self.redact(user))
+            .collect();
+        self.output.render(&users)
     }
 
-    fn remove_user(&self, name: String) -> Result<String> {
+    fn remove_user(&self, name: String, fail_if_missing: bool) -> Result<String> {
         let endpoint_url = &["/api/v2/users/", &name].concat();
-        self.list_the_docs
-            .remove(&endpoint_url, false)
-            .map(|_| name)
+        let previous: Option<get::User> = self.list_the_docs.get(endpoint_url, true)?;
+        let removed = self.list_the_docs.remove(endpoint_url, fail_if_missing)?;
+        let previous = previous.map(|user| self.redact(user));
+        let outcome = Outcome::removal("user", name, previous, removed);
+        self.output.render(&outcome)
     }
 
     fn add_roles(
@@ -267,7 +870,7 @@ impl CommandExecutor {
         file_path: Option<PathBuf>,
     ) -> Result<String> {
         let roles: Vec<_> = match file_path {
-            Some(path) => from_file(path)?,
+            Some(path) => items_from_file_expanded(path, self.input_format)?,
             None => project_role
                 .into_iter()
                 .map(
@@ -282,8 +885,11 @@ impl CommandExecutor {
                 .collect(),
         };
 
+        let previous = self.user_roles(&user_name)?;
         self.list_the_docs.add_roles(&user_name, &roles)?;
-        Ok("".to_owned())
+        let current = self.user_roles(&user_name)?;
+        let outcome = Outcome::update("roles", user_name, previous, current);
+        self.output.render(&outcome)
     }
 
     fn remove_roles(
@@ -291,9 +897,10 @@ impl CommandExecutor {
         user_name: String,
         project_role: Vec<ProjectRole>,
         file_path: Option<PathBuf>,
+        fail_if_missing: bool,
     ) -> Result<String> {
-        let roles: Vec<_> = match file_path {
-            Some(path) => from_file(path)?,
+        let roles: Vec<patch::ProjectRole> = match file_path {
+            Some(path) => items_from_file_expanded(path, self.input_format)?,
             None => project_role
                 .into_iter()
                 .map(
@@ -308,47 +915,434 @@ impl CommandExecutor {
                 .collect(),
         };
 
-        self.list_the_docs.remove_roles(&user_name, &roles)?;
-        Ok("".to_owned())
+        let previous = self.user_roles(&user_name)?;
+        let (roles, missing_roles): (Vec<_>, Vec<_>) = roles.into_iter().partition(|role| {
+            previous.iter().any(|previous_role| {
+                previous_role.role_name == role.role_name
+                    && previous_role.project_code == role.project_code
+            })
+        });
+        if fail_if_missing {
+            if let Some(role) = missing_roles.first() {
+                return Err(Error::InputError(format!(
+                    "User '{}' does not have role '{}' on project '{}'",
+                    user_name, role.role_name, role.project_code
+                )));
+            }
+        }
+
+        // Roles the user does not have are not sent, so that the server does not reject them
+        if !roles.is_empty() {
+            self.list_the_docs.remove_roles(&user_name, &roles)?;
+        }
+        let current = self.user_roles(&user_name)?;
+        let outcome = Outcome::update("roles", user_name, previous, current);
+        self.output.render(&outcome)
     }
 
     fn get_roles(&self, user_name: String) -> Result<String> {
         let endpoint_url = &["/api/v2/users/", &user_name, "/roles"].concat();
-        let roles: Option<Vec<get::Role>> = self.list_the_docs.get(&endpoint_url, true)?;
+        let roles: Option<Vec<get::Role>> = self.list_the_docs.get(endpoint_url, true)?;
         match roles {
-            Some(roles) => Ok(to_string(&roles, self.json_output)),
+            Some(roles) => self.output.render(&roles),
             None => Ok(format!("User with name '{}' not found", user_name)),
         }
     }
-}
 
-//// Utility functions /////
+    /// Actions that fail do not stop the others: they are listed in the report and the exit
+    /// code is set to 1.
+    fn apply(&self, manifest_path: PathBuf, prune: bool) -> Result<CommandResult> {
+        let actions = self.plan_actions(manifest_path, prune)?;
+        let (applied, failed) = self.execute_actions(actions);
+        let exit_code = if failed.is_empty() { 0 } else { 1 };
+        Ok(CommandResult {
+            output: self.output.render(&ApplyReport { applied, failed })?,
+            exit_code,
+        })
+    }
 
-fn from_file<P, R>(path: P) -> Result<R>
-where
-    P: AsRef<Path>,
-    R: serde::de::DeserializeOwned,
-{
-    let file =
-        File::open(path).map_err(|e| Error::InputError(format!("Cannot open file: {}", e)))?;
-    let reader = BufReader::new(file);
-    serde_json::from_reader(reader)
-        .map_err(|e| Error::InputError(format!("Invalid file content: {}", e)))
+    fn plan(&self, manifest_path: PathBuf, prune: bool) -> Result<CommandResult> {
+        let actions = self.plan_actions(manifest_path, prune)?;
+        let output = self.output.render_diff(&actions, || {
+            actions.iter().flat_map(Action::diff_lines).collect()
+        })?;
+        let exit_code = if actions.is_empty() {
+            0
+        } else {
+            DRIFT_EXIT_CODE
+        };
+        Ok(CommandResult { output, exit_code })
+    }
+
+    fn plan_actions(&self, manifest_path: PathBuf, prune: bool) -> Result<Vec<Action>> {
+        let manifest = manifest::load(manifest_path)?;
+        let projects = self.all_projects()?;
+        for warning in manifest::code_warnings(&manifest, &projects) {
+            eprintln!("Warning: {}", warning);
+        }
+        let users = match manifest.users {
+            Some(_) => self.all_users()?,
+            None => vec![],
+        };
+        for warning in manifest::admin_warnings(&manifest, &users) {
+            eprintln!("Warning: {}", warning);
+        }
+        Ok(manifest::plan(&manifest, &projects, &users, prune))
+    }
+
+    /// Responses are checked, so that actions on objects that do not exist anymore fail.
+    fn execute_action(&self, action: &Action) -> Result<()> {
+        let project_not_found =
+            |code: &str| Error::InputError(format!("Project with code '{}' not found", code));
+        match action {
+            Action::CreateProject {
+                project,
+                code,
+                versions,
+            } => {
+                let added_project: get::Project = self
+                    .list_the_docs
+                    .post("/api/v2/projects", project)?
+                    .expect("404 can never be received when adding a project");
+                let endpoint_url =
+                    &["/api/v2/projects/", &added_project.code, "/versions"].concat();
+                for version in versions {
+                    let project: Option<get::Project> =
+                        self.list_the_docs.post(endpoint_url, version)?;
+                    project.ok_or_else(|| project_not_found(&added_project.code))?;
+                }
+                if let Some(code) = code.as_ref().filter(|code| **code != added_project.code) {
+                    return Err(Error::InputError(format!(
+                        "Project '{}' was created with code '{}' instead of '{}'",
+                        project.title, added_project.code, code
+                    )));
+                }
+            }
+            Action::UpdateProject { code, current, .. } => {
+                let endpoint_url = &["/api/v2/projects/", code].concat();
+                let project: Option<get::Project> =
+                    self.list_the_docs.patch(endpoint_url, current)?;
+                project.ok_or_else(|| project_not_found(code))?;
+            }
+            Action::AddVersion { code, version } => {
+                let endpoint_url = &["/api/v2/projects/", code, "/versions"].concat();
+                let project: Option<get::Project> =
+                    self.list_the_docs.post(endpoint_url, version)?;
+                project.ok_or_else(|| project_not_found(code))?;
+            }
+            Action::UpdateVersion {
+                code, version, url, ..
+            } => {
+                self.patch_version(code, version, url.clone())?;
+            }
+            Action::CreateUser { user } => {
+                let added_user: Option<get::User> =
+                    self.list_the_docs.post("/api/v2/users", user)?;
+                added_user.ok_or_else(|| {
+                    Error::InputError(format!("User '{}' could not be created", user.name))
+                })?;
+            }
+            Action::UpdateUser { name, .. } => {
+                return Err(Error::InputError(format!(
+                    "The API cannot change whether user '{}' is an admin",
+                    name
+                )));
+            }
+            Action::AddRoles { user, roles } => self.list_the_docs.add_roles(user, roles)?,
+            Action::RemoveRoles { user, roles } => self.list_the_docs.remove_roles(user, roles)?,
+            Action::RemoveVersion { code, version } => {
+                let endpoint_url = &["/api/v2/projects/", code, "/versions/", version].concat();
+                self.list_the_docs.remove(endpoint_url, true)?;
+            }
+            Action::RemoveUser { name } => {
+                self.list_the_docs
+                    .remove(&["/api/v2/users/", name].concat(), true)?;
+            }
+            Action::RemoveProject { code } => {
+                self.list_the_docs
+                    .remove(&["/api/v2/projects/", code].concat(), true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Objects that cannot be restored do not stop the import: they are listed in the report
+    /// and the exit code is set to 1.
+    fn import(
+        &self,
+        snapshot_path: PathBuf,
+        on_conflict: ConflictStrategy,
+    ) -> Result<CommandResult> {
+        let snapshot = Snapshot::load(snapshot_path)?;
+        let report = self.restore(&snapshot, on_conflict)?;
+        let exit_code = if report.failed.is_empty() { 0 } else { 1 };
+        Ok(CommandResult {
+            output: self.output.render(&report)?,
+            exit_code,
+        })
+    }
+
+    /// Copy the content of this server to `destination`, then check that both servers have
+    /// the same content. The exit code is set to 1 if anything could not be copied.
+    fn migrate(
+        self,
+        destination: ListTheDocs,
+        projects: Vec<String>,
+        rewrite_url: Option<UrlRewrite>,
+        on_conflict: ConflictStrategy,
+    ) -> Result<CommandResult> {
+        let mut snapshot = self.snapshot(false)?;
+        if !projects.is_empty() {
+            snapshot.retain_projects(&projects);
+        }
+        if let Some(rule) = &rewrite_url {
+            snapshot.rewrite_urls(rule);
+        }
+
+        let destination = CommandExecutor {
+            list_the_docs: destination,
+            ..self
+        };
+        let restore = destination.restore(&snapshot, on_conflict)?;
+
+        eprintln!("Verifying {}", destination.list_the_docs.url());
+        let differences = manifest::plan(
+            &snapshot.to_manifest(),
+            &destination.all_projects()?,
+            &destination.all_users()?,
+            false,
+        );
+
+        let exit_code = if restore.failed.is_empty() && differences.is_empty() {
+            0
+        } else {
+            1
+        };
+        let report = MigrationReport {
+            source: snapshot.source.clone(),
+            destination: destination.list_the_docs.url().to_owned(),
+            restore,
+            differences,
+        };
+        Ok(CommandResult {
+            output: destination.output.render(&report)?,
+            exit_code,
+        })
+    }
+
+    fn restore(&self, snapshot: &Snapshot, on_conflict: ConflictStrategy) -> Result<RestoreReport> {
+        let projects = self.all_projects()?;
+        let users = self.all_users()?;
+        let mut report = RestoreReport::new(snapshot, &users);
+
+        let actions = manifest::plan(&snapshot.to_manifest(), &projects, &users, false);
+        let (actions, skipped) = snapshot::resolve_conflicts(actions, on_conflict)?;
+        report.skipped = skipped;
+
+        let (restored, failed) = self.execute_actions(actions);
+        report.restored = restored;
+        report.failed = failed;
+        Ok(report)
+    }
+
+    /// Execute all the actions, showing the progress, and split them into the ones that
+    /// succeeded and the ones that failed.
+    fn execute_actions(&self, actions: Vec<Action>) -> (Vec<Action>, Vec<Failure>) {
+        let mut succeeded = vec![];
+        let mut failed = vec![];
+        let total = actions.len();
+        for (index, action) in actions.into_iter().enumerate() {
+            let (marker, description) = action.diff_lines().remove(0);
+            eprintln!(
+                "[{}/{}] {} {}",
+                index + 1,
+                total,
+                marker.symbol(),
+                description
+            );
+            match self.execute_action(&action) {
+                Ok(()) => succeeded.push(action),
+                Err(e) => failed.push(Failure {
+                    action,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        (succeeded, failed)
+    }
+
+    /// Snapshots are always written in JSON, so that they can be imported back.
+    fn export(&self, exclude_api_keys: bool) -> Result<String> {
+        let snapshot = self.snapshot(!exclude_api_keys)?;
+        Ok(serde_json::to_string_pretty(&snapshot)
+            .unwrap_or_else(|e| panic!("serde serialization failed: {}", e)))
+    }
+
+    fn snapshot(&self, includes_api_keys: bool) -> Result<Snapshot> {
+        Ok(Snapshot::new(
+            self.list_the_docs.url().to_owned(),
+            self.all_projects()?,
+            self.all_users()?,
+            includes_api_keys,
+        ))
+    }
+
+    /// Add the given items one by one. A single item is rendered as usual, while many items
+    /// are summarized, with exit code 1 if any of them failed.
+    fn add_all<T, R, I, A>(&self, items: Vec<T>, id: I, add: A) -> Result<CommandResult>
+    where
+        R: serde::Serialize + Debug,
+        I: Fn(&T) -> String,
+        A: Fn(T) -> Result<R>,
+    {
+        if items.len() == 1 {
+            let added = items.into_iter().map(add).next().expect("one item")?;
+            return Ok(CommandResult {
+                output: self.output.render(&added)?,
+                exit_code: 0,
+            });
+        }
+
+        let mut report = BulkReport::new();
+        for item in items {
+            let id = id(&item);
+            let result = add(item);
+            report.push(id, &result);
+        }
+        let exit_code = if report.failed > 0 { 1 } else { 0 };
+        Ok(CommandResult {
+            output: self.output.render(&report)?,
+            exit_code,
+        })
+    }
+
+    fn stats(&self) -> Result<String> {
+        let stats = stats::stats(&self.all_projects()?, &self.all_users()?);
+        self.output.render(&stats)
+    }
+
+    fn all_projects(&self) -> Result<Vec<get::Project>> {
+        Ok(self
+            .list_the_docs
+            .get("/api/v2/projects", false)?
+            .expect("404 can never be received when getting all projects"))
+    }
+
+    fn all_users(&self) -> Result<Vec<get::User>> {
+        Ok(self
+            .list_the_docs
+            .get("/api/v2/users", true)?
+            .expect("404 can never be received when getting all users"))
+    }
+
+    /// Mask the API keys of a user, unless secrets were explicitly requested.
+    fn redact(&self, mut user: get::User) -> get::User {
+        if !self.show_secrets {
+            secrets::redact_user(&mut user);
+        }
+        user
+    }
+
+    fn user_roles(&self, user_name: &str) -> Result<Vec<get::Role>> {
+        let endpoint_url = &["/api/v2/users/", user_name, "/roles"].concat();
+        let roles: Option<Vec<get::Role>> = self.list_the_docs.get(endpoint_url, true)?;
+        roles.ok_or_else(|| Error::InputError(format!("User with name '{}' not found", user_name)))
+    }
 }
 
-fn to_string<T>(t: &T, json_output: bool) -> String
-where
-    T: serde::Serialize + Debug,
-{
-    if json_output {
-        serde_json::to_string(&t).unwrap_or_else(|e| panic!("serde serialization failed: {}", e))
+/// Compare two snapshot files. The exit code is 2 if they differ, as for `plan`.
+fn diff_snapshots(output: &Output, old: PathBuf, new: PathBuf) -> Result<CommandResult> {
+    let differences = snapshot::diff(&Snapshot::load(old)?, &Snapshot::load(new)?);
+    let text = output.render_diff(&differences, || {
+        differences.iter().map(Difference::diff_line).collect()
+    })?;
+    let exit_code = if differences.is_empty() {
+        0
     } else {
-        format!("{:#?}", t)
+        DRIFT_EXIT_CODE
+    };
+    Ok(CommandResult {
+        output: text,
+        exit_code,
+    })
+}
+
+/// The JSON Schema of the files read by `-f` options, by `--config` and by `apply`.
+fn schema(kind: SchemaKind) -> CommandResult {
+    let schema = match kind {
+        SchemaKind::Project => schema_for!(manifest::Project),
+        SchemaKind::ProjectEnsure => schema_for!(post::Project),
+        SchemaKind::ProjectUpdate => schema_for!(patch::Project),
+        SchemaKind::Version => schema_for!(post::Version),
+        SchemaKind::User => schema_for!(post::User),
+        SchemaKind::Roles => schema_for!(Vec<patch::ProjectRole>),
+        SchemaKind::Config => schema_for!(Config),
+        SchemaKind::Manifest => schema_for!(manifest::Manifest),
+    };
+    CommandResult {
+        output: serde_json::to_string_pretty(&schema)
+            .unwrap_or_else(|e| panic!("serde serialization failed: {}", e)),
+        exit_code: 0,
+    }
+}
+
+// Utility functions //
+
+fn find_version(project: get::Project, name: &str) -> Option<get::Version> {
+    project
+        .versions
+        .into_iter()
+        .find(|version| version.name == name)
+}
+
+/// Expand the `{code}` and `{version}` placeholders in the URL of a version.
+fn expand_version(code: &str, version: post::Version) -> post::Version {
+    let url =
+        input::expand_placeholders(&version.url, &[("code", code), ("version", &version.name)]);
+    post::Version { url, ..version }
+}
+
+/// The logo given on the command line, either as a link or as a local image to embed.
+fn logo_from_args(logo: Option<String>, logo_file: Option<PathBuf>) -> Result<Option<String>> {
+    match logo_file {
+        Some(path) => logo::data_uri(path).map(Some),
+        None => Ok(logo),
+    }
+}
+
+/// Build the definition of a project from the command line arguments, or read it from a file.
+fn project_from_args(
+    title: Option<String>,
+    description: Option<String>,
+    logo: Option<String>,
+    file_path: Option<PathBuf>,
+    input_format: Option<InputFormat>,
+) -> Result<post::Project> {
+    match file_path {
+        Some(path) => from_file_expanded(path, input_format),
+        None => Ok(post::Project {
+            title: title
+                .ok_or_else(|| Error::InputError("Missing compulsory 'title' field".to_owned()))?,
+            description: description.ok_or_else(|| {
+                Error::InputError("Missing compulsory 'description' field".to_owned())
+            })?,
+            logo,
+        }),
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 struct Config {
+    url: Option<String>,
+    api_key: Option<String>,
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct Profile {
     url: String,
     api_key: Option<String>,
 }
@@ -363,8 +1357,13 @@ where
 {
     match config {
         Some(path) => {
-            let config: Config = from_file(path)?;
-            Ok(ListTheDocs::new(config.url, config.api_key))
+            let config: Config = from_file(path, None)?;
+            Ok(ListTheDocs::new(
+                config.url.ok_or_else(|| {
+                    Error::InputError("Missing compulsory 'url' field in config".to_owned())
+                })?,
+                config.api_key,
+            ))
         }
         None => Ok(ListTheDocs::new(
             url.ok_or_else(|| Error::InputError("Missing compulsory url parameter".to_owned()))?,
@@ -372,3 +1371,24 @@ where
         )),
     }
 }
+
+/// Make a client for `profile`, which is either the name of a profile in the configuration
+/// file or the path to a configuration file.
+fn make_profile_client<P>(profile: &str, config: &Option<P>) -> Result<ListTheDocs>
+where
+    P: AsRef<Path>,
+{
+    if let Some(path) = config {
+        let mut config: Config = from_file(path, None)?;
+        if let Some(profile) = config.profiles.remove(profile) {
+            return Ok(ListTheDocs::new(profile.url, profile.api_key));
+        }
+    }
+    if Path::new(profile).is_file() {
+        return make_client(None, None, &Some(profile));
+    }
+    Err(Error::InputError(format!(
+        "'{}' is neither a profile of the configuration file nor a configuration file",
+        profile
+    )))
+}
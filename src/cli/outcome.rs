@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+use super::error::Result;
+
+/// The kind of change a command caused on the server.
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Created,
+    Updated,
+    Removed,
+    Unchanged,
+}
+
+/// The result of a command that modifies the state of the server.
+///
+/// `previous` is the state before the command was executed (`None` if the entity did not exist),
+/// `current` is the state afterwards (`None` if the entity does not exist anymore).
+#[derive(Debug, Serialize)]
+pub struct Outcome<T> {
+    pub entity: &'static str,
+    pub id: String,
+    pub change: Change,
+    pub no_op: bool,
+    pub previous: Option<T>,
+    pub current: Option<T>,
+}
+
+impl<T> Outcome<T> {
+    pub fn removal(entity: &'static str, id: String, previous: Option<T>, removed: bool) -> Self {
+        let change = if removed && previous.is_some() {
+            Change::Removed
+        } else {
+            Change::Unchanged
+        };
+        Outcome::new(entity, id, change, previous, None)
+    }
+
+    pub fn update(entity: &'static str, id: String, previous: T, current: T) -> Self
+    where
+        T: PartialEq,
+    {
+        let change = if previous == current {
+            Change::Unchanged
+        } else {
+            Change::Updated
+        };
+        Outcome::new(entity, id, change, Some(previous), Some(current))
+    }
+
+    pub fn new(
+        entity: &'static str,
+        id: String,
+        change: Change,
+        previous: Option<T>,
+        current: Option<T>,
+    ) -> Self {
+        Outcome {
+            entity,
+            id,
+            change,
+            no_op: change == Change::Unchanged,
+            previous,
+            current,
+        }
+    }
+}
+
+/// The result of adding several objects read from a file, item by item in input order.
+#[derive(Debug, Serialize)]
+pub struct BulkReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub items: Vec<ItemResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemResult {
+    pub id: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BulkReport {
+    pub fn new() -> Self {
+        BulkReport {
+            succeeded: 0,
+            failed: 0,
+            items: vec![],
+        }
+    }
+
+    pub fn push<T>(&mut self, id: String, result: &Result<T>) {
+        let error = result.as_ref().err().map(ToString::to_string);
+        if error.is_some() {
+            self.failed += 1;
+        } else {
+            self.succeeded += 1;
+        }
+        self.items.push(ItemResult {
+            id,
+            success: error.is_none(),
+            error,
+        });
+    }
+}
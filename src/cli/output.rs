@@ -0,0 +1,434 @@
+use handlebars::Handlebars;
+use serde_json::Value;
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::{self, Stdio};
+
+use super::command_line::ColorChoice;
+use super::error::{Error, Result};
+use super::fuzzy;
+use super::versions;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[1;32m";
+const YELLOW: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+const TEMPLATE_NAME: &str = "output";
+const MARKDOWN_TEMPLATE: &str = include_str!("templates/markdown.hbs");
+const HTML_TEMPLATE: &str = include_str!("templates/html.hbs");
+
+/// Renders the results of the commands in the format requested from the command line.
+pub struct Output {
+    pub json: bool,
+    pub colored: bool,
+    template: Option<Template>,
+}
+
+/// A user-defined way to render results, based on Handlebars.
+struct Template {
+    registry: Handlebars<'static>,
+    /// Format strings are applied to each element when the result is a list
+    per_item: bool,
+}
+
+/// Whether a line of a diff describes something added, changed or removed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Marker {
+    Add,
+    Change,
+    Remove,
+}
+
+impl Marker {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Marker::Add => "+",
+            Marker::Change => "~",
+            Marker::Remove => "-",
+        }
+    }
+}
+
+impl Output {
+    pub fn new(
+        json: bool,
+        color: ColorChoice,
+        template: Option<String>,
+        format_string: Option<String>,
+    ) -> Result<Output> {
+        let colored = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal(),
+        };
+        let template = match (template, format_string) {
+            (Some(template), _) => Some(Template::from_name_or_file(&template)?),
+            (None, Some(format_string)) => Some(Template::new(&format_string, false, true)?),
+            (None, None) => None,
+        };
+        Ok(Output {
+            json,
+            colored,
+            template,
+        })
+    }
+
+    pub fn render<T>(&self, t: &T) -> Result<String>
+    where
+        T: serde::Serialize + Debug,
+    {
+        if let Some(template) = &self.template {
+            template.render(t)
+        } else if self.json {
+            Ok(serde_json::to_string(&t)
+                .unwrap_or_else(|e| panic!("serde serialization failed: {}", e)))
+        } else if self.colored {
+            Ok(colorize(&format!("{:#?}", t)))
+        } else {
+            Ok(format!("{:#?}", t))
+        }
+    }
+
+    /// Render `t` as JSON or with the template, or as the given text in the human-readable
+    /// output.
+    pub fn render_text<T, F>(&self, t: &T, text: F) -> Result<String>
+    where
+        T: serde::Serialize + Debug,
+        F: FnOnce() -> String,
+    {
+        if self.json || self.template.is_some() {
+            self.render(t)
+        } else {
+            Ok(text())
+        }
+    }
+
+    /// Highlight the words of `text` that match any of the search `terms`, if colors are enabled.
+    pub fn highlight(&self, text: &str, terms: &[String]) -> String {
+        if !self.colored {
+            return text.to_owned();
+        }
+        text.split_inclusive(char::is_whitespace)
+            .map(|token| {
+                let word = token.trim_end();
+                if terms
+                    .iter()
+                    .any(|term| fuzzy::match_score(term, word) > 0.0)
+                {
+                    [YELLOW, word, RESET, &token[word.len()..]].concat()
+                } else {
+                    token.to_owned()
+                }
+            })
+            .collect()
+    }
+
+    /// Render a set of changes as a diff in the human-readable output, with `+`, `~` and `-`
+    /// markers for additions, changes and removals. JSON and templates render `changes`.
+    pub fn render_diff<T, F>(&self, changes: &T, diff_lines: F) -> Result<String>
+    where
+        T: serde::Serialize + Debug,
+        F: FnOnce() -> Vec<(Marker, String)>,
+    {
+        if self.json || self.template.is_some() {
+            return self.render(changes);
+        }
+
+        let lines = diff_lines();
+        if lines.is_empty() {
+            return Ok("No changes".to_owned());
+        }
+        // Indented lines are details of the line above them, so they are not counted
+        let count = |marker| {
+            lines
+                .iter()
+                .filter(|(m, line)| *m == marker && !line.starts_with(' '))
+                .count()
+        };
+        let summary = format!(
+            "{} to add, {} to change, {} to remove",
+            count(Marker::Add),
+            count(Marker::Change),
+            count(Marker::Remove)
+        );
+        let mut text: Vec<String> = lines
+            .iter()
+            .map(|(marker, line)| {
+                let color = match marker {
+                    Marker::Add => GREEN,
+                    Marker::Change => YELLOW,
+                    Marker::Remove => RED,
+                };
+                let line = [marker.symbol(), " ", line].concat();
+                if self.colored {
+                    paint(&line, color)
+                } else {
+                    line
+                }
+            })
+            .collect();
+        text.push(String::new());
+        text.push(summary);
+        Ok(text.join("\n"))
+    }
+}
+
+impl Template {
+    /// `name_or_path` is either one of the built-in templates (`markdown` or `html`)
+    /// or the path to a Handlebars template. HTML escaping is enabled for `.html` files.
+    fn from_name_or_file(name_or_path: &str) -> Result<Template> {
+        match name_or_path {
+            "markdown" => Template::new(MARKDOWN_TEMPLATE, false, false),
+            "html" => Template::new(HTML_TEMPLATE, true, false),
+            path => {
+                let template = fs::read_to_string(path)
+                    .map_err(|e| Error::InputError(format!("Cannot open template: {}", e)))?;
+                let is_html = Path::new(path)
+                    .extension()
+                    .is_some_and(|extension| extension == "html" || extension == "htm");
+                Template::new(&template, is_html, false)
+            }
+        }
+    }
+
+    fn new(template: &str, escape_html: bool, per_item: bool) -> Result<Template> {
+        let mut registry = Handlebars::new();
+        if !escape_html {
+            registry.register_escape_fn(handlebars::no_escape);
+        }
+        registry.register_helper("items", Box::new(helpers::items));
+        registry.register_helper("columns", Box::new(helpers::columns));
+        registry.register_helper("cell", Box::new(helpers::cell));
+        registry.register_helper("md", Box::new(helpers::md));
+        registry.register_helper("upper", Box::new(helpers::upper));
+        registry.register_helper("lower", Box::new(helpers::lower));
+        registry.register_helper("join", Box::new(helpers::join));
+        registry.register_helper("json", Box::new(helpers::json));
+        registry.register_helper("latest", Box::new(helpers::latest));
+        registry
+            .register_template_string(TEMPLATE_NAME, template)
+            .map_err(|e| Error::InputError(format!("Invalid template: {}", e)))?;
+        Ok(Template { registry, per_item })
+    }
+
+    fn render<T>(&self, t: &T) -> Result<String>
+    where
+        T: serde::Serialize,
+    {
+        let value =
+            serde_json::to_value(t).unwrap_or_else(|e| panic!("serde serialization failed: {}", e));
+        let render = |value: &Value| {
+            self.registry
+                .render(TEMPLATE_NAME, value)
+                .map_err(|e| Error::InputError(format!("Cannot render template: {}", e)))
+        };
+        match &value {
+            Value::Array(values) if self.per_item => values
+                .iter()
+                .map(render)
+                .collect::<Result<Vec<_>>>()
+                .map(|lines| lines.join("\n")),
+            value => render(value),
+        }
+    }
+}
+
+/// Helpers available in the templates, in addition to the Handlebars built-in ones.
+mod helpers {
+    use handlebars::handlebars_helper;
+    use serde_json::Value;
+
+    handlebars_helper!(items: |value: Json| match value {
+        Value::Array(_) => value.clone(),
+        Value::Null => Value::Array(vec![]),
+        _ => Value::Array(vec![value.clone()]),
+    });
+
+    handlebars_helper!(columns: |values: Json| match values {
+        Value::Array(values) => column_names(values),
+        _ => vec![],
+    });
+
+    handlebars_helper!(cell: |value: Json| cell_text(value));
+
+    handlebars_helper!(md: |text: str| text.replace('|', "\\|").replace('\n', " "));
+
+    handlebars_helper!(upper: |text: str| text.to_uppercase());
+
+    handlebars_helper!(lower: |text: str| text.to_lowercase());
+
+    handlebars_helper!(join: |values: array, separator: str| values
+        .iter()
+        .map(cell_text)
+        .collect::<Vec<_>>()
+        .join(separator));
+
+    handlebars_helper!(json: |value: Json| value.to_string());
+
+    handlebars_helper!(latest: |versions: array| super::versions::latest(
+        versions
+            .iter()
+            .filter_map(|version| version.get("name").and_then(Value::as_str))
+    )
+    .unwrap_or_default()
+    .to_owned());
+
+    /// The fields of all the objects in `values`, in order of appearance, so that items with
+    /// different fields can be shown in the same table.
+    fn column_names(values: &[Value]) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for object in values.iter().filter_map(Value::as_object) {
+            for name in object.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// A compact textual representation of a value, suitable for a table cell.
+    pub fn cell_text(value: &Value) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            Value::Array(values) => values.iter().map(cell_text).collect::<Vec<_>>().join(", "),
+            Value::Object(object) => match (
+                object.get("name"),
+                object.get("role_name"),
+                object.get("project_code"),
+            ) {
+                (Some(name), _, _) => cell_text(name),
+                (None, Some(role_name), Some(project_code)) => {
+                    [cell_text(role_name), cell_text(project_code)].join("/")
+                }
+                _ => value.to_string(),
+            },
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Highlight admin flags, invalid API keys and the latest version of each project
+/// in the human-readable output.
+fn colorize(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut colored_lines = Vec::with_capacity(lines.len());
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        let trimmed = line.trim_start();
+        if trimmed == "versions: [" {
+            let indentation = &line[..line.len() - trimmed.len()];
+            let end = lines[index..]
+                .iter()
+                .position(|line| line.starts_with(&[indentation, "]"].concat()))
+                .map_or(lines.len(), |position| index + position);
+            colored_lines.push(line.to_owned());
+            colored_lines.extend(highlight_latest_version(&lines[index + 1..end]));
+            index = end;
+        } else {
+            colored_lines.push(match trimmed {
+                "is_admin: true," => paint(line, YELLOW),
+                "is_valid: false," => paint(line, RED),
+                _ => line.to_owned(),
+            });
+            index += 1;
+        }
+    }
+    colored_lines.join("\n")
+}
+
+fn highlight_latest_version(lines: &[&str]) -> Vec<String> {
+    let names = lines.iter().filter_map(|line| version_name(line));
+    let latest = versions::latest(names);
+    lines
+        .iter()
+        .map(|line| match version_name(line) {
+            Some(name) if Some(name) == latest => paint(line, GREEN),
+            _ => (*line).to_owned(),
+        })
+        .collect()
+}
+
+fn version_name(line: &str) -> Option<&str> {
+    line.trim_start()
+        .strip_prefix("name: \"")
+        .and_then(|name| name.strip_suffix("\","))
+}
+
+fn paint(line: &str, color: &str) -> String {
+    let trimmed = line.trim_start();
+    let indentation = &line[..line.len() - trimmed.len()];
+    [indentation, color, trimmed, RESET].concat()
+}
+
+/// Print the output of a command, through the pager if standard output is a terminal.
+///
+/// The pager is taken from `$PAGER` and defaults to `less`. As `git` does, `less` is told
+/// to quit immediately if the output fits one screen and to keep colors.
+pub fn print(text: &str, use_pager: bool) {
+    if use_pager && io::stdout().is_terminal() && page(text).is_ok() {
+        return;
+    }
+    println!("{}", text);
+}
+
+fn page(text: &str) -> io::Result<()> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".to_owned());
+    let mut pager_words = pager.split_whitespace();
+    let program = match pager_words.next() {
+        Some(program) if program != "cat" => program,
+        _ => return Err(io::Error::new(io::ErrorKind::NotFound, "pager disabled")),
+    };
+
+    let mut command = process::Command::new(program);
+    command.args(pager_words).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything
+        let _ = writeln!(stdin, "{}", text);
+    }
+    child.wait().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mixed_items() -> Value {
+        json!([
+            {"id": "a", "change": "created"},
+            {"id": "b", "change": "failed", "error": "Conflict"},
+        ])
+    }
+
+    #[test]
+    fn markdown_table_has_a_column_for_every_field() {
+        let template = Template::from_name_or_file("markdown").unwrap();
+        assert_eq!(
+            template.render(&mixed_items()).unwrap(),
+            "| change | id | error |\n\
+             | --- | --- | --- |\n\
+             | created | a |  |\n\
+             | failed | b | Conflict |\n"
+        );
+    }
+
+    #[test]
+    fn html_table_has_a_column_for_every_field() {
+        let template = Template::from_name_or_file("html").unwrap();
+        let html = template.render(&mixed_items()).unwrap();
+        assert!(html.contains("<tr><th>change</th><th>id</th><th>error</th></tr>"));
+        assert!(html.contains("<tr><td>created</td><td>a</td><td></td></tr>"));
+        assert!(html.contains("<tr><td>failed</td><td>b</td><td>Conflict</td></tr>"));
+    }
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+
+use super::entities::get;
+use super::fuzzy;
+
+/// A project matching a search, with how relevant it is.
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub score: f64,
+    /// The fields where the search terms were found
+    pub matches: Vec<&'static str>,
+    pub project: get::Project,
+}
+
+/// Find the projects matching all `terms` in their title, code, description or version names,
+/// the most relevant first. Matches in titles and codes count more than the others.
+pub fn search(projects: Vec<get::Project>, terms: &[String]) -> Vec<SearchResult> {
+    let mut results: Vec<_> = projects
+        .into_iter()
+        .filter_map(|project| {
+            let mut score = 0.0;
+            let mut matches = vec![];
+            for term in terms {
+                let versions = project.versions.iter().map(|version| version.name.as_str());
+                let fields = [
+                    ("title", 2.0 * fuzzy::match_score(term, &project.title)),
+                    ("code", 2.0 * fuzzy::match_score(term, &project.code)),
+                    (
+                        "description",
+                        fuzzy::match_score(term, &project.description),
+                    ),
+                    (
+                        "versions",
+                        versions
+                            .map(|name| fuzzy::match_score(term, name))
+                            .fold(0.0, f64::max),
+                    ),
+                ];
+                let best = fields.iter().map(|(_, score)| *score).fold(0.0, f64::max);
+                if best == 0.0 {
+                    return None;
+                }
+                score += best;
+                for (field, field_score) in fields.iter() {
+                    if *field_score > 0.0 && !matches.contains(field) {
+                        matches.push(*field);
+                    }
+                }
+            }
+            Some(SearchResult {
+                score,
+                matches,
+                project,
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn projects() -> Vec<get::Project> {
+        serde_json::from_value(json!([
+            {
+                "title": "Backend",
+                "code": "backend",
+                "description": "The server, with its documentation tooling",
+                "versions": [{"name": "1.0.0", "url": "http://docs/backend/1.0.0"}]
+            },
+            {
+                "title": "Doc Tooling",
+                "code": "doc-tooling",
+                "description": "Builds the documentation site",
+                "versions": [{"name": "2.0.0", "url": "http://docs/doc-tooling/2.0.0"}]
+            },
+            {
+                "title": "Frontend",
+                "code": "frontend",
+                "description": "The web interface",
+                "versions": []
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn codes(results: &[SearchResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|result| result.project.code.as_str())
+            .collect()
+    }
+
+    fn terms(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|term| term.to_string()).collect()
+    }
+
+    #[test]
+    fn titles_and_codes_count_more_than_descriptions() {
+        let results = search(projects(), &terms(&["tooling"]));
+        assert_eq!(codes(&results), vec!["doc-tooling", "backend"]);
+        assert_eq!(results[0].score, 2.0);
+        assert_eq!(results[0].matches, vec!["title", "code"]);
+        assert_eq!(results[1].score, 1.0);
+        assert_eq!(results[1].matches, vec!["description"]);
+    }
+
+    #[test]
+    fn all_terms_must_match() {
+        let results = search(projects(), &terms(&["tooling", "server"]));
+        assert_eq!(codes(&results), vec!["backend"]);
+        assert_eq!(results[0].score, 2.0);
+        assert!(search(projects(), &terms(&["tooling", "kubernetes"])).is_empty());
+    }
+
+    #[test]
+    fn versions_and_misspellings_match() {
+        let results = search(projects(), &terms(&["2.0.0"]));
+        assert_eq!(codes(&results), vec!["doc-tooling"]);
+        assert_eq!(results[0].matches, vec!["versions"]);
+
+        let results = search(projects(), &terms(&["frontedn"]));
+        assert_eq!(codes(&results), vec!["frontend"]);
+        assert!(results[0].score > 0.0 && results[0].score < 2.0);
+    }
+}
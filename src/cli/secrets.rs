@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+use super::entities::get;
+
+/// Number of characters of an API key that are shown when the key is masked.
+const VISIBLE_PREFIX_LENGTH: usize = 4;
+
+/// Mask a secret, keeping only a short prefix so that keys can still be told apart.
+pub fn mask(secret: &str) -> String {
+    let prefix: String = secret.chars().take(VISIBLE_PREFIX_LENGTH).collect();
+    [&prefix, "********"].concat()
+}
+
+/// Mask the API keys of a user.
+pub fn redact_user(user: &mut get::User) {
+    for api_key in &mut user.api_keys {
+        api_key.key = mask(&api_key.key);
+    }
+}
+
+/// Mask the API keys found in a server response, so that it can be safely shown in errors.
+///
+/// Responses that are not JSON are returned unchanged.
+pub fn scrub(response: &str) -> String {
+    match serde_json::from_str::<Value>(response) {
+        Ok(mut value) => {
+            scrub_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => response.to_owned(),
+    }
+}
+
+fn scrub_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                match field {
+                    Value::String(secret) if name == "key" || name == "api_key" => {
+                        *secret = mask(secret)
+                    }
+                    _ => scrub_value(field),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_keeps_a_short_prefix() {
+        assert_eq!(mask("0123456789"), "0123********");
+        assert_eq!(mask("ab"), "ab********");
+    }
+
+    #[test]
+    fn scrub_masks_keys_in_nested_values() {
+        let response = r#"{"users":[{"name":"alice","api_keys":[{"key":"0123456789"}]}],"error":{"api_key":"abcdefghij"}}"#;
+        let scrubbed: Value = serde_json::from_str(&scrub(response)).unwrap();
+        assert_eq!(
+            scrubbed,
+            serde_json::json!({
+                "users": [{"name": "alice", "api_keys": [{"key": "0123********"}]}],
+                "error": {"api_key": "abcd********"}
+            })
+        );
+    }
+
+    #[test]
+    fn scrub_leaves_other_fields_unchanged() {
+        let response = r#"[{"name":"key","keys":["0123456789"],"key":42}]"#;
+        let scrubbed: Value = serde_json::from_str(&scrub(response)).unwrap();
+        assert_eq!(
+            scrubbed,
+            serde_json::json!([{"name": "key", "keys": ["0123456789"], "key": 42}])
+        );
+    }
+
+    #[test]
+    fn scrub_returns_responses_that_are_not_json_unchanged() {
+        assert_eq!(scrub("Not Found"), "Not Found");
+        assert_eq!(scrub(""), "");
+    }
+}
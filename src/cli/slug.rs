@@ -0,0 +1,290 @@
+use super::entities::get;
+
+/// Derive the code of a project from its title, as the server does (with the default options
+/// of python-slugify): non-ASCII characters are transliterated, HTML character references are
+/// decoded and any run of characters that are not lowercase letters or digits becomes a dash.
+pub fn code_for(title: &str) -> String {
+    // Emoji have no transliteration in unidecode, used by python-slugify
+    let title: String = title
+        .replace('\'', "-")
+        .chars()
+        .filter(|c| !matches!(c, '\u{1f300}'..='\u{1faff}'))
+        .collect();
+    let title = deunicode::deunicode(&title);
+    let title = decode_references(&title).to_lowercase();
+    // Transliteration and decoding can produce new apostrophes, which are dropped
+    let title = title.replace('\'', "");
+
+    let characters: Vec<char> = title.chars().collect();
+    let mut code = String::with_capacity(title.len());
+    for (index, character) in characters.iter().enumerate() {
+        // Thousands separators are dropped, as in "1,000"
+        let is_digit_separator = *character == ','
+            && index > 0
+            && characters[index - 1].is_ascii_digit()
+            && characters.get(index + 1).is_some_and(char::is_ascii_digit);
+        if character.is_ascii_lowercase() || character.is_ascii_digit() {
+            code.push(*character);
+        } else if !is_digit_separator && !code.ends_with('-') {
+            code.push('-');
+        }
+    }
+    code.trim_matches('-').to_owned()
+}
+
+/// Decode HTML character references such as `&amp;`, `&#233;` and `&#xE9;`.
+///
+/// As python-slugify decodes them after transliterating the title, the decoded characters are
+/// only decomposed, so that e.g. `&eacute;` becomes `e-` rather than `e`.
+fn decode_references(text: &str) -> String {
+    let text = replace_references(text, "&", |name| {
+        ENTITIES
+            .iter()
+            .find(|(entity, _)| *entity == name)
+            .map(|(_, code_point)| *code_point)
+    });
+    let text = replace_references(&text, "&#", |digits| {
+        digits
+            .chars()
+            .all(|c| c.is_ascii_digit())
+            .then(|| digits.parse().ok())
+            .flatten()
+    });
+    replace_references(&text, "&#x", |digits| {
+        digits
+            .chars()
+            .all(|c| c.is_ascii_hexdigit())
+            .then(|| u32::from_str_radix(digits, 16).ok())
+            .flatten()
+    })
+}
+
+/// Replace the references made of `prefix`, a name and `;` with the character that `decode`
+/// returns for the name, leaving the references it does not know as they are.
+fn replace_references<F>(text: &str, prefix: &str, decode: F) -> String
+where
+    F: Fn(&str) -> Option<u32>,
+{
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(prefix) {
+        decoded.push_str(&rest[..start]);
+        let reference = &rest[start + prefix.len()..];
+        let character = reference.find(';').and_then(|end| {
+            let character = decode(&reference[..end]).and_then(char::from_u32)?;
+            Some((character, end))
+        });
+        match character {
+            Some((character, end)) => {
+                decoded.push_str(&decompose(character));
+                rest = &reference[end + 1..];
+            }
+            None => {
+                decoded.push_str(prefix);
+                rest = reference;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Approximate the compatibility decomposition (NFKD) of a character, with a dash in place
+/// of the parts that are not ASCII: accented Latin letters keep their base letter.
+fn decompose(character: char) -> String {
+    if character.is_ascii() {
+        return character.to_string();
+    }
+    if let Some((_, decomposed)) = COMPATIBILITY
+        .iter()
+        .find(|(compatible, _)| *compatible == character)
+    {
+        return (*decomposed).to_owned();
+    }
+    match deunicode::deunicode_char(character) {
+        Some(base) if is_accented_letter(character) => [base, "-"].concat(),
+        _ => "-".to_owned(),
+    }
+}
+
+/// Whether a character is a Latin letter decomposed into a base letter and diacritics.
+#[rustfmt::skip]
+fn is_accented_letter(character: char) -> bool {
+    matches!(
+        character,
+        '\u{c0}'..='\u{c5}' | '\u{c7}'..='\u{cf}' | '\u{d1}'..='\u{d6}' | '\u{d9}'..='\u{dd}'
+            | '\u{e0}'..='\u{e5}' | '\u{e7}'..='\u{ef}' | '\u{f1}'..='\u{f6}'
+            | '\u{f9}'..='\u{fd}' | '\u{ff}'..='\u{10f}' | '\u{112}'..='\u{125}'
+            | '\u{128}'..='\u{130}' | '\u{134}'..='\u{137}' | '\u{139}'..='\u{13e}'
+            | '\u{143}'..='\u{148}' | '\u{14c}'..='\u{151}' | '\u{154}'..='\u{165}'
+            | '\u{168}'..='\u{17e}' | '\u{1a0}'..='\u{1a1}' | '\u{1af}'..='\u{1b0}'
+            | '\u{1cd}'..='\u{1dc}' | '\u{1de}'..='\u{1e1}' | '\u{1e6}'..='\u{1ed}' | '\u{1f0}'
+            | '\u{1f4}'..='\u{1f5}' | '\u{1f8}'..='\u{1fb}' | '\u{200}'..='\u{21b}'
+            | '\u{21e}'..='\u{21f}' | '\u{226}'..='\u{233}' | '\u{1e00}'..='\u{1e99}' | '\u{1e9b}'
+            | '\u{1ea0}'..='\u{1ef9}'
+    )
+}
+
+/// Warn about titles whose code is already taken by another project, or which would
+/// result in a code that looks quite different from the title.
+pub fn code_warnings(title: &str, projects: &[get::Project]) -> Vec<String> {
+    let code = code_for(title);
+    let mut warnings = vec![];
+    if code.is_empty() {
+        warnings.push(format!("The title '{}' results in an empty code", title));
+        return warnings;
+    }
+    if let Some(existing) = projects.iter().find(|project| project.code == code) {
+        warnings.push(format!(
+            "The code '{}' of '{}' is already taken by project '{}'",
+            code, title, existing.title
+        ));
+    }
+    let plain = title
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-");
+    if code != plain {
+        warnings.push(format!("The code of '{}' will be '{}'", title, code));
+    }
+    warnings
+}
+
+/// Characters with HTML 4 named references whose decomposition has ASCII letters or digits,
+/// besides accented letters.
+const COMPATIBILITY: &[(char, &str)] = &[
+    ('\u{aa}', "a"),
+    ('\u{b2}', "2"),
+    ('\u{b3}', "3"),
+    ('\u{b9}', "1"),
+    ('\u{ba}', "o"),
+    ('\u{bc}', "1-4"),
+    ('\u{bd}', "1-2"),
+    ('\u{be}', "3-4"),
+    ('\u{2111}', "I"),
+    ('\u{211c}', "R"),
+    ('\u{2122}', "TM"),
+];
+
+/// The HTML 4 named character references, as decoded by python-slugify.
+#[rustfmt::skip]
+const ENTITIES: &[(&str, u32)] = &[
+    ("quot", 34), ("amp", 38), ("lt", 60), ("gt", 62), ("nbsp", 160), ("iexcl", 161), ("cent", 162),
+    ("pound", 163), ("curren", 164), ("yen", 165), ("brvbar", 166), ("sect", 167), ("uml", 168),
+    ("copy", 169), ("ordf", 170), ("laquo", 171), ("not", 172), ("shy", 173), ("reg", 174),
+    ("macr", 175), ("deg", 176), ("plusmn", 177), ("sup2", 178), ("sup3", 179), ("acute", 180),
+    ("micro", 181), ("para", 182), ("middot", 183), ("cedil", 184), ("sup1", 185), ("ordm", 186),
+    ("raquo", 187), ("frac14", 188), ("frac12", 189), ("frac34", 190), ("iquest", 191),
+    ("Agrave", 192), ("Aacute", 193), ("Acirc", 194), ("Atilde", 195), ("Auml", 196),
+    ("Aring", 197), ("AElig", 198), ("Ccedil", 199), ("Egrave", 200), ("Eacute", 201),
+    ("Ecirc", 202), ("Euml", 203), ("Igrave", 204), ("Iacute", 205), ("Icirc", 206), ("Iuml", 207),
+    ("ETH", 208), ("Ntilde", 209), ("Ograve", 210), ("Oacute", 211), ("Ocirc", 212),
+    ("Otilde", 213), ("Ouml", 214), ("times", 215), ("Oslash", 216), ("Ugrave", 217),
+    ("Uacute", 218), ("Ucirc", 219), ("Uuml", 220), ("Yacute", 221), ("THORN", 222), ("szlig", 223),
+    ("agrave", 224), ("aacute", 225), ("acirc", 226), ("atilde", 227), ("auml", 228),
+    ("aring", 229), ("aelig", 230), ("ccedil", 231), ("egrave", 232), ("eacute", 233),
+    ("ecirc", 234), ("euml", 235), ("igrave", 236), ("iacute", 237), ("icirc", 238), ("iuml", 239),
+    ("eth", 240), ("ntilde", 241), ("ograve", 242), ("oacute", 243), ("ocirc", 244),
+    ("otilde", 245), ("ouml", 246), ("divide", 247), ("oslash", 248), ("ugrave", 249),
+    ("uacute", 250), ("ucirc", 251), ("uuml", 252), ("yacute", 253), ("thorn", 254), ("yuml", 255),
+    ("OElig", 338), ("oelig", 339), ("Scaron", 352), ("scaron", 353), ("Yuml", 376), ("fnof", 402),
+    ("circ", 710), ("tilde", 732), ("Alpha", 913), ("Beta", 914), ("Gamma", 915), ("Delta", 916),
+    ("Epsilon", 917), ("Zeta", 918), ("Eta", 919), ("Theta", 920), ("Iota", 921), ("Kappa", 922),
+    ("Lambda", 923), ("Mu", 924), ("Nu", 925), ("Xi", 926), ("Omicron", 927), ("Pi", 928),
+    ("Rho", 929), ("Sigma", 931), ("Tau", 932), ("Upsilon", 933), ("Phi", 934), ("Chi", 935),
+    ("Psi", 936), ("Omega", 937), ("alpha", 945), ("beta", 946), ("gamma", 947), ("delta", 948),
+    ("epsilon", 949), ("zeta", 950), ("eta", 951), ("theta", 952), ("iota", 953), ("kappa", 954),
+    ("lambda", 955), ("mu", 956), ("nu", 957), ("xi", 958), ("omicron", 959), ("pi", 960),
+    ("rho", 961), ("sigmaf", 962), ("sigma", 963), ("tau", 964), ("upsilon", 965), ("phi", 966),
+    ("chi", 967), ("psi", 968), ("omega", 969), ("thetasym", 977), ("upsih", 978), ("piv", 982),
+    ("ensp", 8194), ("emsp", 8195), ("thinsp", 8201), ("zwnj", 8204), ("zwj", 8205), ("lrm", 8206),
+    ("rlm", 8207), ("ndash", 8211), ("mdash", 8212), ("lsquo", 8216), ("rsquo", 8217),
+    ("sbquo", 8218), ("ldquo", 8220), ("rdquo", 8221), ("bdquo", 8222), ("dagger", 8224),
+    ("Dagger", 8225), ("bull", 8226), ("hellip", 8230), ("permil", 8240), ("prime", 8242),
+    ("Prime", 8243), ("lsaquo", 8249), ("rsaquo", 8250), ("oline", 8254), ("frasl", 8260),
+    ("euro", 8364), ("image", 8465), ("weierp", 8472), ("real", 8476), ("trade", 8482),
+    ("alefsym", 8501), ("larr", 8592), ("uarr", 8593), ("rarr", 8594), ("darr", 8595),
+    ("harr", 8596), ("crarr", 8629), ("lArr", 8656), ("uArr", 8657), ("rArr", 8658), ("dArr", 8659),
+    ("hArr", 8660), ("forall", 8704), ("part", 8706), ("exist", 8707), ("empty", 8709),
+    ("nabla", 8711), ("isin", 8712), ("notin", 8713), ("ni", 8715), ("prod", 8719), ("sum", 8721),
+    ("minus", 8722), ("lowast", 8727), ("radic", 8730), ("prop", 8733), ("infin", 8734),
+    ("ang", 8736), ("and", 8743), ("or", 8744), ("cap", 8745), ("cup", 8746), ("int", 8747),
+    ("there4", 8756), ("sim", 8764), ("cong", 8773), ("asymp", 8776), ("ne", 8800), ("equiv", 8801),
+    ("le", 8804), ("ge", 8805), ("sub", 8834), ("sup", 8835), ("nsub", 8836), ("sube", 8838),
+    ("supe", 8839), ("oplus", 8853), ("otimes", 8855), ("perp", 8869), ("sdot", 8901),
+    ("lceil", 8968), ("rceil", 8969), ("lfloor", 8970), ("rfloor", 8971), ("lang", 9001),
+    ("rang", 9002), ("loz", 9674), ("spades", 9824), ("clubs", 9827), ("hearts", 9829),
+    ("diams", 9830),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected codes are the results of python-slugify with its default options
+    fn assert_codes(cases: &[(&str, &str)]) {
+        for (title, code) in cases {
+            assert_eq!(code_for(title), *code, "code of {:?}", title);
+        }
+    }
+
+    #[test]
+    fn separators() {
+        assert_codes(&[
+            ("My Project", "my-project"),
+            ("This is a test ---", "this-is-a-test"),
+            ("___This is a test___", "this-is-a-test"),
+            ("jaja---lol-méméméoo--a", "jaja-lol-mememeoo-a"),
+            ("10 | 20 %", "10-20"),
+            ("C++ & C#: a (short) tour!", "c-c-a-short-tour"),
+            ("i love 🦄", "i-love"),
+            ("i love🦄you", "i-loveyou"),
+            ("!!!", ""),
+        ]);
+    }
+
+    #[test]
+    fn apostrophes() {
+        assert_codes(&[
+            ("It's a 'test'", "it-s-a-test"),
+            ("C'est déjà l'été.", "c-est-deja-l-ete"),
+            ("Компьютер", "kompiuter"),
+        ]);
+    }
+
+    #[test]
+    fn transliteration() {
+        assert_codes(&[
+            (
+                "Nín hǎo. Wǒ shì zhōng guó rén",
+                "nin-hao-wo-shi-zhong-guo-ren",
+            ),
+            ("影師嗎", "ying-shi-ma"),
+            ("Über Straße", "uber-strasse"),
+        ]);
+    }
+
+    #[test]
+    fn thousands_separators() {
+        assert_codes(&[
+            ("1,000 reasons you are #1", "1000-reasons-you-are-1"),
+            ("Version 1,2", "version-12"),
+            ("one, two", "one-two"),
+            ("1, 2", "1-2"),
+        ]);
+    }
+
+    #[test]
+    fn html_references() {
+        assert_codes(&[
+            ("foo &amp; bar", "foo-bar"),
+            ("&#381;", "z"),
+            ("&#x17D;", "z"),
+            ("r&eacute;sum&eacute;", "re-sume"),
+            ("Don&#39;t", "dont"),
+            ("Brand&trade;", "brandtm"),
+            ("&unknown; &#xZZ; &#12", "unknown-xzz-12"),
+        ]);
+    }
+}
@@ -0,0 +1,616 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::entities::{get, patch, post};
+use super::error::{Error, Result};
+use super::input;
+use super::manifest::{self, Action, Failure, Manifest};
+use super::output::Marker;
+use super::timestamp::Timestamp;
+
+/// Identifies snapshot files, so that other JSON files are not mistaken for them.
+pub const FORMAT: &str = "listthedocs-snapshot";
+
+/// The version of the snapshot format. It must be increased when the format changes
+/// in a way that older versions of the CLI cannot read.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A full copy of the content of a List The Docs instance.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: String,
+    pub format_version: u32,
+    pub created_at: Timestamp,
+    /// The URL of the server the snapshot was taken from
+    pub source: String,
+    /// If false, the API keys of the users have been left out
+    pub includes_api_keys: bool,
+    pub projects: Vec<get::Project>,
+    pub users: Vec<get::User>,
+}
+
+impl Snapshot {
+    pub fn new(
+        source: String,
+        projects: Vec<get::Project>,
+        mut users: Vec<get::User>,
+        includes_api_keys: bool,
+    ) -> Snapshot {
+        if !includes_api_keys {
+            users.iter_mut().for_each(|user| user.api_keys.clear());
+        }
+        Snapshot {
+            format: FORMAT.to_owned(),
+            format_version: FORMAT_VERSION,
+            created_at: Timestamp::now(),
+            source,
+            includes_api_keys,
+            projects,
+            users,
+        }
+    }
+
+    /// Load a snapshot, checking that the file is a snapshot in a supported format.
+    pub fn load<P>(path: P) -> Result<Snapshot>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let snapshot: Snapshot = input::from_file(path, None)?;
+        if snapshot.format != FORMAT {
+            return Err(Error::InputError(format!(
+                "'{}' is not a List The Docs snapshot",
+                path.display()
+            )));
+        }
+        if snapshot.format_version > FORMAT_VERSION {
+            return Err(Error::InputError(format!(
+                "Snapshot format version {} is not supported (the latest supported is {})",
+                snapshot.format_version, FORMAT_VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Keep only the given projects, and the users holding roles on them.
+    pub fn retain_projects(&mut self, codes: &[String]) {
+        self.projects
+            .retain(|project| codes.contains(&project.code));
+        for user in &mut self.users {
+            user.roles.retain(|role| codes.contains(&role.project_code));
+        }
+        self.users.retain(|user| !user.roles.is_empty());
+    }
+
+    /// Rewrite the URLs of all versions according to `rule`.
+    pub fn rewrite_urls(&mut self, rule: &UrlRewrite) {
+        for version in self
+            .projects
+            .iter_mut()
+            .flat_map(|project| project.versions.iter_mut())
+        {
+            if let Some(rest) = version.url.strip_prefix(&rule.from) {
+                version.url = [&rule.to, rest].concat();
+            }
+        }
+    }
+
+    /// A manifest describing exactly the content of the snapshot.
+    pub fn to_manifest(&self) -> Manifest {
+        let projects = self
+            .projects
+            .iter()
+            .map(|project| manifest::Project {
+                title: project.title.clone(),
+                code: Some(project.code.clone()),
+                description: project.description.clone(),
+                logo: project.logo.clone(),
+                versions: project
+                    .versions
+                    .iter()
+                    .map(|version| post::Version {
+                        name: version.name.clone(),
+                        url: version.url.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        let users = self
+            .users
+            .iter()
+            .map(|user| manifest::User {
+                name: user.name.clone(),
+                is_admin: Some(user.is_admin),
+                roles: user
+                    .roles
+                    .iter()
+                    .map(|role| patch::ProjectRole {
+                        role_name: role.role_name.clone(),
+                        project_code: role.project_code.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Manifest {
+            projects: Some(projects),
+            users: Some(users),
+        }
+    }
+}
+
+/// What to do when an object already exists on the server with a different content.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictStrategy {
+    /// Leave the object on the server as it is
+    Skip,
+    /// Replace the object on the server with the given one (`update` is accepted as a synonym)
+    Overwrite,
+    /// Stop before changing anything
+    Fail,
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(ConflictStrategy::Skip),
+            "overwrite" | "update" => Ok(ConflictStrategy::Overwrite),
+            "fail" => Ok(ConflictStrategy::Fail),
+            _ => std::result::Result::Err(format!("Invalid conflict strategy: {}", s)),
+        }
+    }
+}
+
+/// Replace a prefix of URLs, given on the command line as `OLD_PREFIX=NEW_PREFIX`.
+#[derive(Debug)]
+pub struct UrlRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+impl FromStr for UrlRewrite {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() => Ok(UrlRewrite {
+                from: from.to_owned(),
+                to: to.to_owned(),
+            }),
+            _ => std::result::Result::Err(format!(
+                "Invalid URL rewrite rule (expected OLD_PREFIX=NEW_PREFIX): {}",
+                s
+            )),
+        }
+    }
+}
+
+/// The result of restoring a snapshot.
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    pub restored: Vec<Action>,
+    /// Changes to existing objects that were skipped because of the conflict strategy
+    pub skipped: Vec<Action>,
+    /// Changes that the server rejected, with the reason
+    pub failed: Vec<Failure>,
+    /// Parts of the snapshot that cannot be restored through the API
+    pub not_restored: Vec<String>,
+}
+
+impl RestoreReport {
+    /// Start a report for `snapshot`, listing what cannot be restored on a server
+    /// whose users are `existing_users`.
+    pub fn new(snapshot: &Snapshot, existing_users: &[get::User]) -> RestoreReport {
+        let mut not_restored = vec![];
+        for user in &snapshot.users {
+            if !user.api_keys.is_empty() {
+                not_restored.push(format!(
+                    "API keys of user '{}': they are generated by the server",
+                    user.name
+                ));
+            }
+            let existing = existing_users
+                .iter()
+                .find(|existing| existing.name == user.name);
+            if let Some(existing) = existing {
+                if existing.is_admin != user.is_admin {
+                    not_restored.push(format!(
+                        "Admin flag of user '{}': it cannot be changed for existing users",
+                        user.name
+                    ));
+                }
+            }
+        }
+        RestoreReport {
+            restored: vec![],
+            skipped: vec![],
+            failed: vec![],
+            not_restored,
+        }
+    }
+}
+
+/// Split the actions needed to restore a snapshot into the ones to execute and the ones
+/// to skip, according to the conflict strategy.
+pub fn resolve_conflicts(
+    actions: Vec<Action>,
+    strategy: ConflictStrategy,
+) -> Result<(Vec<Action>, Vec<Action>)> {
+    let (conflicts, actions): (Vec<_>, Vec<_>) = actions.into_iter().partition(|action| {
+        matches!(
+            action,
+            Action::UpdateProject { .. } | Action::UpdateVersion { .. }
+        )
+    });
+    match strategy {
+        ConflictStrategy::Skip => Ok((actions, conflicts)),
+        ConflictStrategy::Overwrite => {
+            let mut actions = actions;
+            actions.extend(conflicts);
+            actions.sort_by_key(Action::order);
+            Ok((actions, vec![]))
+        }
+        ConflictStrategy::Fail => match conflicts.first() {
+            Some(conflict) => {
+                let description = conflict.diff_lines().remove(0).1;
+                Err(Error::InputError(format!(
+                    "Conflict with existing {} ({} conflicts in total)",
+                    description,
+                    conflicts.len()
+                )))
+            }
+            None => Ok((actions, vec![])),
+        },
+    }
+}
+
+/// The result of copying the content of a server to another one.
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    pub source: String,
+    pub destination: String,
+    pub restore: RestoreReport,
+    /// What still differs between the source and the destination after the migration
+    pub differences: Vec<Action>,
+}
+
+/// A difference between two snapshots.
+///
+/// Objects are identified by `id`: the code for projects, `code/name` for versions,
+/// the name for users and `user:ROLE_NAME/code` for roles.
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Difference {
+    Added {
+        entity: &'static str,
+        id: String,
+    },
+    Removed {
+        entity: &'static str,
+        id: String,
+    },
+    Changed {
+        entity: &'static str,
+        id: String,
+        field: &'static str,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Difference {
+    pub fn diff_line(&self) -> (Marker, String) {
+        match self {
+            Difference::Added { entity, id } => (Marker::Add, format!("{} '{}'", entity, id)),
+            Difference::Removed { entity, id } => (Marker::Remove, format!("{} '{}'", entity, id)),
+            Difference::Changed {
+                entity,
+                id,
+                field,
+                old,
+                new,
+            } => (
+                Marker::Change,
+                format!("{} '{}': {} {} -> {}", entity, id, field, old, new),
+            ),
+        }
+    }
+}
+
+/// Compare two snapshots, listing what changed from `old` to `new`.
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Difference> {
+    let mut differences = vec![];
+
+    for old_project in &old.projects {
+        match new.projects.iter().find(|p| p.code == old_project.code) {
+            Some(new_project) => diff_project(&mut differences, old_project, new_project),
+            None => {
+                differences.push(Difference::Removed {
+                    entity: "project",
+                    id: old_project.code.clone(),
+                });
+                for version in &old_project.versions {
+                    differences.push(Difference::Removed {
+                        entity: "version",
+                        id: [&old_project.code, "/", &version.name].concat(),
+                    });
+                }
+            }
+        }
+    }
+    for new_project in &new.projects {
+        if !old.projects.iter().any(|p| p.code == new_project.code) {
+            differences.push(Difference::Added {
+                entity: "project",
+                id: new_project.code.clone(),
+            });
+            for version in &new_project.versions {
+                differences.push(Difference::Added {
+                    entity: "version",
+                    id: [&new_project.code, "/", &version.name].concat(),
+                });
+            }
+        }
+    }
+
+    for old_user in &old.users {
+        match new.users.iter().find(|u| u.name == old_user.name) {
+            Some(new_user) => diff_user(&mut differences, old_user, new_user),
+            None => {
+                differences.push(Difference::Removed {
+                    entity: "user",
+                    id: old_user.name.clone(),
+                });
+                diff_roles(&mut differences, &old_user.name, &old_user.roles, &[]);
+            }
+        }
+    }
+    for new_user in &new.users {
+        if !old.users.iter().any(|u| u.name == new_user.name) {
+            differences.push(Difference::Added {
+                entity: "user",
+                id: new_user.name.clone(),
+            });
+            diff_roles(&mut differences, &new_user.name, &[], &new_user.roles);
+        }
+    }
+
+    differences
+}
+
+fn diff_project(differences: &mut Vec<Difference>, old: &get::Project, new: &get::Project) {
+    let fields = [
+        (
+            "title",
+            Value::from(old.title.as_str()),
+            Value::from(new.title.as_str()),
+        ),
+        (
+            "description",
+            Value::from(old.description.as_str()),
+            Value::from(new.description.as_str()),
+        ),
+        (
+            "logo",
+            Value::from(old.logo.clone()),
+            Value::from(new.logo.clone()),
+        ),
+    ];
+    for (field, old_value, new_value) in fields.iter() {
+        if old_value != new_value {
+            differences.push(Difference::Changed {
+                entity: "project",
+                id: old.code.clone(),
+                field,
+                old: old_value.clone(),
+                new: new_value.clone(),
+            });
+        }
+    }
+
+    for old_version in &old.versions {
+        let id = [&old.code, "/", &old_version.name].concat();
+        match new.versions.iter().find(|v| v.name == old_version.name) {
+            Some(new_version) if new_version.url != old_version.url => {
+                differences.push(Difference::Changed {
+                    entity: "version",
+                    id,
+                    field: "url",
+                    old: Value::from(old_version.url.as_str()),
+                    new: Value::from(new_version.url.as_str()),
+                })
+            }
+            Some(_) => {}
+            None => differences.push(Difference::Removed {
+                entity: "version",
+                id,
+            }),
+        }
+    }
+    for new_version in &new.versions {
+        if !old.versions.iter().any(|v| v.name == new_version.name) {
+            differences.push(Difference::Added {
+                entity: "version",
+                id: [&new.code, "/", &new_version.name].concat(),
+            });
+        }
+    }
+}
+
+fn diff_user(differences: &mut Vec<Difference>, old: &get::User, new: &get::User) {
+    if old.is_admin != new.is_admin {
+        differences.push(Difference::Changed {
+            entity: "user",
+            id: old.name.clone(),
+            field: "is_admin",
+            old: Value::from(old.is_admin),
+            new: Value::from(new.is_admin),
+        });
+    }
+    diff_roles(differences, &old.name, &old.roles, &new.roles);
+}
+
+fn diff_roles(
+    differences: &mut Vec<Difference>,
+    user: &str,
+    old_roles: &[get::Role],
+    new_roles: &[get::Role],
+) {
+    let role_id = |role: &get::Role| format!("{}:{}/{}", user, role.role_name, role.project_code);
+    for role in old_roles.iter().filter(|role| !new_roles.contains(role)) {
+        differences.push(Difference::Removed {
+            entity: "role",
+            id: role_id(role),
+        });
+    }
+    for role in new_roles.iter().filter(|role| !old_roles.contains(role)) {
+        differences.push(Difference::Added {
+            entity: "role",
+            id: role_id(role),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(projects: Value, users: Value) -> Snapshot {
+        Snapshot::new(
+            "http://localhost".to_owned(),
+            serde_json::from_value(projects).unwrap(),
+            serde_json::from_value(users).unwrap(),
+            false,
+        )
+    }
+
+    fn project(code: &str, description: &str, versions: Value) -> Value {
+        json!({
+            "title": code,
+            "code": code,
+            "description": description,
+            "logo": null,
+            "versions": versions
+        })
+    }
+
+    fn user(name: &str, is_admin: bool, roles: Value) -> Value {
+        json!({
+            "name": name,
+            "is_admin": is_admin,
+            "created_at": "2020-03-05T11:20:30",
+            "api_keys": [],
+            "roles": roles
+        })
+    }
+
+    fn lines(differences: &[Difference]) -> Vec<String> {
+        differences
+            .iter()
+            .map(|difference| {
+                let (marker, line) = difference.diff_line();
+                format!("{} {}", marker.symbol(), line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_differences() {
+        let old = snapshot(
+            json!([project(
+                "a",
+                "A",
+                json!([{"name": "1.0", "url": "http://a/1.0"}])
+            )]),
+            json!([user(
+                "alice",
+                false,
+                json!([{"role_name": "PROJECT_MANAGER", "project_code": "a"}])
+            )]),
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn changed_fields_versions_and_roles() {
+        let old = snapshot(
+            json!([project(
+                "a",
+                "A",
+                json!([
+                    {"name": "1.0", "url": "http://a/1.0"},
+                    {"name": "2.0", "url": "http://a/2.0"}
+                ])
+            )]),
+            json!([user(
+                "alice",
+                false,
+                json!([{"role_name": "PROJECT_MANAGER", "project_code": "a"}])
+            )]),
+        );
+        let new = snapshot(
+            json!([project(
+                "a",
+                "New A",
+                json!([
+                    {"name": "2.0", "url": "http://b/2.0"},
+                    {"name": "3.0", "url": "http://a/3.0"}
+                ])
+            )]),
+            json!([user(
+                "alice",
+                true,
+                json!([{"role_name": "PROJECT_OWNER", "project_code": "a"}])
+            )]),
+        );
+        assert_eq!(
+            lines(&diff(&old, &new)),
+            vec![
+                "~ project 'a': description \"A\" -> \"New A\"",
+                "- version 'a/1.0'",
+                "~ version 'a/2.0': url \"http://a/2.0\" -> \"http://b/2.0\"",
+                "+ version 'a/3.0'",
+                "~ user 'alice': is_admin false -> true",
+                "- role 'alice:PROJECT_MANAGER/a'",
+                "+ role 'alice:PROJECT_OWNER/a'",
+            ]
+        );
+    }
+
+    #[test]
+    fn added_and_removed_objects_list_their_content() {
+        let with_content = snapshot(
+            json!([project(
+                "a",
+                "A",
+                json!([{"name": "1.0", "url": "http://a/1.0"}])
+            )]),
+            json!([user(
+                "alice",
+                false,
+                json!([{"role_name": "PROJECT_MANAGER", "project_code": "a"}])
+            )]),
+        );
+        let empty = snapshot(json!([]), json!([]));
+        assert_eq!(
+            lines(&diff(&empty, &with_content)),
+            vec![
+                "+ project 'a'",
+                "+ version 'a/1.0'",
+                "+ user 'alice'",
+                "+ role 'alice:PROJECT_MANAGER/a'",
+            ]
+        );
+        assert_eq!(
+            lines(&diff(&with_content, &empty)),
+            vec![
+                "- project 'a'",
+                "- version 'a/1.0'",
+                "- user 'alice'",
+                "- role 'alice:PROJECT_MANAGER/a'",
+            ]
+        );
+    }
+}
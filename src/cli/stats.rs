@@ -0,0 +1,172 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::entities::get;
+
+/// A summary of the content of the server, to spot what needs attention.
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub projects: usize,
+    pub versions: usize,
+    /// Not given if there are no projects
+    pub versions_per_project: Option<Distribution>,
+    pub projects_without_versions: Vec<String>,
+    pub users: usize,
+    pub admins: usize,
+    pub invalid_api_keys: usize,
+    /// The number of roles held by users on each project
+    pub roles_per_project: BTreeMap<String, usize>,
+    /// Projects on which no user holds any role
+    pub projects_without_owner: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Distribution {
+    pub min: usize,
+    pub median: f64,
+    pub max: usize,
+}
+
+impl Distribution {
+    fn new(mut values: Vec<usize>) -> Option<Distribution> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let count = values.len();
+        // The two middle values are the same one if the count is odd
+        let median = (values[(count - 1) / 2] + values[count / 2]) as f64 / 2.0;
+        Some(Distribution {
+            min: values[0],
+            median,
+            max: values[count - 1],
+        })
+    }
+}
+
+pub fn stats(projects: &[get::Project], users: &[get::User]) -> Stats {
+    let mut roles_per_project: BTreeMap<String, usize> = projects
+        .iter()
+        .map(|project| (project.code.clone(), 0))
+        .collect();
+    for role in users.iter().flat_map(|user| &user.roles) {
+        if let Some(roles) = roles_per_project.get_mut(&role.project_code) {
+            *roles += 1;
+        }
+    }
+
+    Stats {
+        projects: projects.len(),
+        versions: projects.iter().map(|project| project.versions.len()).sum(),
+        versions_per_project: Distribution::new(
+            projects
+                .iter()
+                .map(|project| project.versions.len())
+                .collect(),
+        ),
+        projects_without_versions: projects
+            .iter()
+            .filter(|project| project.versions.is_empty())
+            .map(|project| project.code.clone())
+            .collect(),
+        users: users.len(),
+        admins: users.iter().filter(|user| user.is_admin).count(),
+        invalid_api_keys: users
+            .iter()
+            .flat_map(|user| &user.api_keys)
+            .filter(|api_key| !api_key.is_valid)
+            .count(),
+        projects_without_owner: roles_per_project
+            .iter()
+            .filter(|(_, roles)| **roles == 0)
+            .map(|(code, _)| code.clone())
+            .collect(),
+        roles_per_project,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn project(code: &str, versions: usize) -> serde_json::Value {
+        let versions: Vec<_> = (0..versions)
+            .map(|i| json!({"name": format!("{}.0", i), "url": "http://docs"}))
+            .collect();
+        json!({"title": code, "code": code, "description": "", "versions": versions})
+    }
+
+    fn user(name: &str, is_admin: bool, roles: &[&str]) -> serde_json::Value {
+        let roles: Vec<_> = roles
+            .iter()
+            .map(|code| json!({"role_name": "PROJECT_MANAGER", "project_code": code}))
+            .collect();
+        json!({
+            "name": name,
+            "is_admin": is_admin,
+            "created_at": "2020-03-05T11:20:30",
+            "api_keys": [
+                {"key": "KEY", "is_valid": !is_admin, "created_at": "2020-03-05T11:20:30"}
+            ],
+            "roles": roles
+        })
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        let odd = Distribution::new(vec![5, 1, 3]).unwrap();
+        assert_eq!((odd.min, odd.median, odd.max), (1, 3.0, 5));
+
+        let even = Distribution::new(vec![4, 1, 2, 8]).unwrap();
+        assert_eq!((even.min, even.median, even.max), (1, 3.0, 8));
+
+        let single = Distribution::new(vec![7]).unwrap();
+        assert_eq!((single.min, single.median, single.max), (7, 7.0, 7));
+    }
+
+    #[test]
+    fn empty_catalog() {
+        let stats = stats(&[], &[]);
+        assert_eq!(stats.projects, 0);
+        assert_eq!(stats.versions, 0);
+        assert!(stats.versions_per_project.is_none());
+        assert!(stats.projects_without_owner.is_empty());
+        assert!(stats.roles_per_project.is_empty());
+    }
+
+    #[test]
+    fn summary_of_projects_and_users() {
+        let projects: Vec<get::Project> =
+            serde_json::from_value(json!([project("a", 2), project("b", 0), project("c", 1)]))
+                .unwrap();
+        let users: Vec<get::User> = serde_json::from_value(json!([
+            user("root", true, &[]),
+            user("alice", false, &["a", "removed"]),
+            user("bob", false, &["a"])
+        ]))
+        .unwrap();
+
+        let stats = stats(&projects, &users);
+        assert_eq!(stats.projects, 3);
+        assert_eq!(stats.versions, 3);
+        let distribution = stats.versions_per_project.unwrap();
+        assert_eq!(
+            (distribution.min, distribution.median, distribution.max),
+            (0, 1.0, 2)
+        );
+        assert_eq!(stats.projects_without_versions, vec!["b"]);
+        assert_eq!((stats.users, stats.admins), (3, 1));
+        assert_eq!(stats.invalid_api_keys, 1);
+        // Roles on projects that do not exist are not counted
+        assert_eq!(
+            stats.roles_per_project,
+            BTreeMap::from([
+                ("a".to_owned(), 2),
+                ("b".to_owned(), 0),
+                ("c".to_owned(), 0)
+            ])
+        );
+        assert_eq!(stats.projects_without_owner, vec!["b", "c"]);
+    }
+}
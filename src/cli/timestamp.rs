@@ -0,0 +1,234 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A point in time, as returned by the server (e.g., in the creation date of users).
+///
+/// The original text is kept, so that it is serialized as the server returned it, even when it
+/// is in a format that cannot be parsed. The human-readable output shows parsed timestamps in
+/// local time together with their age (e.g., `2020-03-05 11:20 (3 months ago)`).
+#[derive(Clone)]
+pub struct Timestamp {
+    text: String,
+    time: Option<DateTime<Utc>>,
+}
+
+impl Timestamp {
+    pub fn now() -> Timestamp {
+        let time = Utc::now();
+        Timestamp {
+            text: time.to_rfc3339(),
+            time: Some(time),
+        }
+    }
+
+    /// Parse a timestamp, keeping the original text if its format is not recognized.
+    fn lenient(text: String) -> Timestamp {
+        let time = parse(&text);
+        Timestamp { text, time }
+    }
+}
+
+/// Parse RFC 3339 and RFC 2822 timestamps, ISO 8601 timestamps without timezone (assumed to be
+/// UTC, also with a space instead of `T`) and plain dates (assumed to be midnight UTC).
+fn parse(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(timestamp) = DateTime::parse_from_rfc2822(s) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(timestamp) = NaiveDateTime::from_str(s) {
+        return Some(timestamp.and_utc());
+    }
+    if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f") {
+        return Some(timestamp.and_utc());
+    }
+    if let Ok(date) = NaiveDate::from_str(s) {
+        return Some(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    None
+}
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    /// Unlike the timestamps returned by the server, those given by the user must be valid.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse(s) {
+            Some(time) => Ok(Timestamp {
+                text: s.to_owned(),
+                time: Some(time),
+            }),
+            None => Result::Err(format!("Invalid date or timestamp: {}", s)),
+        }
+    }
+}
+
+/// Timestamps are equal if they are the same point in time, however they are written.
+/// Those that could not be parsed are only equal to the same text.
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.time, other.time) {
+            (Some(time), Some(other_time)) => time == other_time,
+            (None, None) => self.text == other.text,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Timestamp {}
+
+/// Timestamps that could not be parsed cannot be compared with any other.
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.time, other.time) {
+            (Some(time), Some(other_time)) => Some(time.cmp(&other_time)),
+            _ if self == other => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.time {
+            Some(time) => write!(
+                f,
+                "{} ({})",
+                time.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                age(time, Utc::now())
+            ),
+            None => write!(f, "{}", self.text),
+        }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Timestamp::lenient)
+    }
+}
+
+/// Describe how long ago `then` was, with the largest sensible unit (e.g., `3 months ago`).
+fn age(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - then).num_seconds();
+    if seconds < 0 {
+        return "in the future".to_owned();
+    }
+
+    let units = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+    for (unit_seconds, unit_name) in units.iter() {
+        let count = seconds / unit_seconds;
+        if count > 0 {
+            let plural = if count == 1 { "" } else { "s" };
+            return format!("{} {}{} ago", count, unit_name, plural);
+        }
+    }
+    "just now".to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(text: &str) -> Option<DateTime<Utc>> {
+        Timestamp::lenient(text.to_owned()).time
+    }
+
+    #[test]
+    fn parse_known_formats() {
+        let expected = Some(Utc.with_ymd_and_hms(2020, 3, 5, 11, 20, 30).unwrap());
+        assert_eq!(utc("2020-03-05T11:20:30Z"), expected);
+        assert_eq!(utc("2020-03-05T12:20:30+01:00"), expected);
+        assert_eq!(utc("Thu, 05 Mar 2020 11:20:30 GMT"), expected);
+        assert_eq!(utc("2020-03-05T11:20:30"), expected);
+        assert_eq!(utc("2020-03-05 11:20:30"), expected);
+        assert_eq!(
+            utc("2020-03-05 11:20:30.250"),
+            Some(
+                Utc.with_ymd_and_hms(2020, 3, 5, 11, 20, 30).unwrap()
+                    + chrono::Duration::milliseconds(250)
+            )
+        );
+        assert_eq!(
+            utc("2020-03-05"),
+            Some(Utc.with_ymd_and_hms(2020, 3, 5, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn unknown_formats_are_kept() {
+        let timestamp: Timestamp = serde_json::from_str("\"5th of March\"").unwrap();
+        assert_eq!(timestamp.time, None);
+        assert_eq!(format!("{:?}", timestamp), "5th of March");
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            "\"5th of March\""
+        );
+        assert!(Timestamp::from_str("5th of March").is_err());
+    }
+
+    #[test]
+    fn original_text_is_serialized() {
+        let timestamp: Timestamp =
+            serde_json::from_str("\"Thu, 05 Mar 2020 11:20:30 GMT\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            "\"Thu, 05 Mar 2020 11:20:30 GMT\""
+        );
+    }
+
+    #[test]
+    fn unparsed_timestamps_are_not_ordered() {
+        let parsed = Timestamp::from_str("2020-03-05").unwrap();
+        let unparsed = Timestamp::lenient("unknown".to_owned());
+        assert!(parsed < Timestamp::from_str("2020-03-06").unwrap());
+        assert_eq!(parsed.partial_cmp(&unparsed), None);
+    }
+
+    #[test]
+    fn the_same_instant_is_equal_however_it_is_written() {
+        let created_at = Timestamp::lenient("2021-01-01T00:00:00".to_owned());
+        for after in [
+            "2021-01-01",
+            "2021-01-01T00:00:00Z",
+            "2021-01-01T01:00:00+01:00",
+        ] {
+            let after = Timestamp::from_str(after).unwrap();
+            assert_eq!(created_at, after);
+            assert_eq!(created_at.partial_cmp(&after), Some(Ordering::Equal));
+        }
+        assert!(created_at < Timestamp::from_str("2021-01-01T00:00:01Z").unwrap());
+    }
+
+    #[test]
+    fn age_uses_largest_unit() {
+        let now = Utc.with_ymd_and_hms(2020, 6, 5, 0, 0, 0).unwrap();
+        assert_eq!(age(now - chrono::Duration::days(92), now), "3 months ago");
+        assert_eq!(age(now - chrono::Duration::minutes(1), now), "1 minute ago");
+        assert_eq!(age(now, now), "just now");
+        assert_eq!(age(now + chrono::Duration::days(1), now), "in the future");
+    }
+}
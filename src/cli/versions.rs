@@ -0,0 +1,111 @@
+use std::cmp::Ordering;
+
+/// Compare two version names, so that e.g. `1.10.0` comes after `1.9.2`.
+///
+/// Names are split in components at every non-alphanumeric character. Numeric components are
+/// compared as numbers, the others lexicographically. A version with additional non-numeric
+/// components is considered a pre-release, so `1.0.0-rc1` comes before `1.0.0`.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let mut a_components = components(a);
+    let mut b_components = components(b);
+    loop {
+        match (a_components.next(), b_components.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(a), None) => {
+                return if is_numeric(a) {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+            (None, Some(b)) => {
+                return if is_numeric(b) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (Some(a), Some(b)) => {
+                let ordering = match (is_numeric(a), is_numeric(b)) {
+                    (true, true) => compare_numbers(a, b),
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => a.cmp(b),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Find the latest among a list of version names.
+pub fn latest<'a, I>(names: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    names.into_iter().max_by(|a, b| compare(a, b))
+}
+
+fn components(name: &str) -> impl Iterator<Item = &str> {
+    name.trim_start_matches(['v', 'V'])
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|component| !component.is_empty())
+}
+
+fn is_numeric(component: &str) -> bool {
+    component.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Compare two numeric components of any length, without parsing them.
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_components_are_compared_as_numbers() {
+        assert_eq!(compare("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare("1.9.2", "1.10.0"), Ordering::Less);
+        assert_eq!(compare("1.01", "1.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn pre_releases_come_before_releases() {
+        assert_eq!(compare("1.0.0-rc1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare("1.0.0", "1.0.0-rc1"), Ordering::Greater);
+        assert_eq!(compare("1.0.0-rc1", "1.0.0-rc2"), Ordering::Less);
+        assert_eq!(compare("1.0.0-rc1", "1.0.0.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn prefix_v_is_ignored() {
+        assert_eq!(compare("v1.2", "1.2"), Ordering::Equal);
+        assert_eq!(compare("V1.10", "v1.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn numbers_larger_than_u64_are_compared_as_numbers() {
+        assert_eq!(
+            compare("1.99999999999999999999", "1.100000000000000000000"),
+            Ordering::Less
+        );
+        assert_eq!(compare("1.18446744073709551616", "1.2"), Ordering::Greater);
+        assert_eq!(compare("1.18446744073709551616", "1.rc"), Ordering::Greater);
+    }
+
+    #[test]
+    fn latest_version() {
+        assert_eq!(
+            latest(vec!["1.9", "1.10", "1.10-rc1", "v0.9"]),
+            Some("1.10")
+        );
+        assert_eq!(latest(vec![]), None);
+    }
+}
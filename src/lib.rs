@@ -1,6 +1,6 @@
 mod cli;
 
 pub use cli::{
-    execute_command, Command, Error, Opt, ProjectCommand, ProjectRole, RoleCommand, UserCommand,
-    VersionCommand,
+    execute_command, options_from_args, print, Command, CommandResult, Error, Opt, ProjectCommand,
+    ProjectRole, RoleCommand, UserCommand, VersionCommand,
 };
@@ -1,9 +1,14 @@
 mod cli;
 use cli::Result;
+use std::process;
 
 fn main() -> Result<()> {
     let command_line_options = cli::options_from_args();
+    let use_pager = !command_line_options.no_pager;
     let result = cli::execute_command(command_line_options)?;
-    println!("{}", result);
+    cli::print(&result.output, use_pager);
+    if result.exit_code != 0 {
+        process::exit(result.exit_code);
+    }
     Ok(())
 }
@@ -24,7 +24,7 @@ fn add_update_remove_project() -> Result<(), serde_json::Error> {
     let exe = find_exe();
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -37,12 +37,12 @@ fn add_update_remove_project() -> Result<(), serde_json::Error> {
     let _ = cmd.output().expect("listthedocs output");
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "project", "get", project_title]);
+    cmd.args(["-j", "-u", URL, "project", "get", project_title]);
     let output = cmd.output().expect("listthedocs output");
     assert!(String::from_utf8_lossy(&output.stdout).contains("not found"));
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -58,7 +58,7 @@ fn add_update_remove_project() -> Result<(), serde_json::Error> {
     assert_eq!(result, project);
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -75,7 +75,7 @@ fn add_update_remove_project() -> Result<(), serde_json::Error> {
     assert_eq!(result.logo, Some("mylogo".to_owned()));
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -86,19 +86,119 @@ fn add_update_remove_project() -> Result<(), serde_json::Error> {
         project_title,
     ]);
     let output = cmd.output().expect("listthedocs output");
+    let result: Outcome<Project> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
+    assert_eq!(result.change, "removed");
     assert_eq!(
-        String::from_utf8_lossy(&output.stdout).trim(),
-        project_title
+        result.previous.map(|p| p.code),
+        Some(project_title.to_owned())
     );
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "project", "get", project_title]);
+    cmd.args([
+        "-j",
+        "-u",
+        URL,
+        "-a",
+        API_KEY,
+        "project",
+        "remove",
+        project_title,
+    ]);
+    let output = cmd.output().expect("listthedocs output");
+    let result: Outcome<Project> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
+    assert!(result.no_op);
+
+    let mut cmd = process::Command::new(&exe);
+    cmd.args([
+        "-j",
+        "-u",
+        URL,
+        "-a",
+        API_KEY,
+        "project",
+        "remove",
+        project_title,
+        "--fail-if-missing",
+    ]);
+    let output = cmd.output().expect("listthedocs output");
+    assert!(!output.status.success());
+
+    let mut cmd = process::Command::new(&exe);
+    cmd.args(["-j", "-u", URL, "project", "get", project_title]);
     let output = cmd.output().expect("listthedocs output");
     assert!(String::from_utf8_lossy(&output.stdout).contains("not found"));
 
     Ok(())
 }
 
+#[test]
+#[ignore]
+fn ensure_project() -> Result<(), serde_json::Error> {
+    let project_title = "test-ensured-project";
+
+    let exe = find_exe();
+
+    let mut cmd = process::Command::new(&exe);
+    cmd.args([
+        "-j",
+        "-u",
+        URL,
+        "-a",
+        API_KEY,
+        "project",
+        "remove",
+        project_title,
+    ]);
+    let _ = cmd.output().expect("listthedocs output");
+
+    let ensure = |description: &str| {
+        let mut cmd = process::Command::new(&exe);
+        cmd.args([
+            "-j",
+            "-u",
+            URL,
+            "-a",
+            API_KEY,
+            "project",
+            "ensure",
+            project_title,
+            description,
+        ]);
+        let output = cmd.output().expect("listthedocs output");
+        serde_json::from_str::<Outcome<Project>>(&String::from_utf8_lossy(&output.stdout))
+    };
+
+    let result = ensure("testing project")?;
+    assert_eq!(result.change, "created");
+    assert_eq!(result.previous, None);
+
+    let result = ensure("testing project")?;
+    assert_eq!(result.change, "unchanged");
+    assert!(result.no_op);
+
+    let result = ensure("updated testing project")?;
+    assert_eq!(result.change, "updated");
+    assert_eq!(
+        result.current.map(|p| p.description),
+        Some("updated testing project".to_owned())
+    );
+
+    let mut cmd = process::Command::new(&exe);
+    cmd.args([
+        "-j",
+        "-u",
+        URL,
+        "-a",
+        API_KEY,
+        "project",
+        "remove",
+        project_title,
+    ]);
+    let _ = cmd.output().expect("listthedocs output");
+
+    Ok(())
+}
+
 #[test]
 #[ignore]
 fn add_update_remove_version() -> Result<(), serde_json::Error> {
@@ -120,7 +220,7 @@ fn add_update_remove_version() -> Result<(), serde_json::Error> {
     let exe = find_exe();
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -133,7 +233,7 @@ fn add_update_remove_version() -> Result<(), serde_json::Error> {
     let _ = cmd.output().expect("listthedocs output");
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -147,7 +247,7 @@ fn add_update_remove_version() -> Result<(), serde_json::Error> {
     let _ = cmd.output().expect("listthedocs output");
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -160,12 +260,13 @@ fn add_update_remove_version() -> Result<(), serde_json::Error> {
         version_url,
     ]);
     let output = cmd.output().expect("listthedocs output");
-    let result: Project = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
-    assert_eq!(result, project);
+    let result: Outcome<Version> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
+    assert_eq!(result.change, "created");
+    assert_eq!(result.current.as_ref(), project.versions.first());
 
     let updated_url = "http://updated.com";
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -182,7 +283,7 @@ fn add_update_remove_version() -> Result<(), serde_json::Error> {
     assert_eq!(result.versions[0].url, updated_url);
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -194,7 +295,19 @@ fn add_update_remove_version() -> Result<(), serde_json::Error> {
         version_name,
     ]);
     let output = cmd.output().expect("listthedocs output");
-    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), version_name);
+    let result: Outcome<Version> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
+    assert_eq!(result.change, "removed");
+    assert_eq!(
+        result.previous,
+        Some(Version {
+            name: version_name.to_owned(),
+            url: updated_url.to_owned(),
+        })
+    );
+
+    // Removing a version that does not exist fails
+    let output = cmd.output().expect("listthedocs output");
+    assert!(!output.status.success());
 
     Ok(())
 }
@@ -216,11 +329,11 @@ fn add_get_remove_user() -> Result<(), serde_json::Error> {
     let exe = find_exe();
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "-a", API_KEY, "user", "remove", user_name]);
+    cmd.args(["-j", "-u", URL, "-a", API_KEY, "user", "remove", user_name]);
     let _ = cmd.output().expect("listthedocs output");
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -242,9 +355,28 @@ fn add_get_remove_user() -> Result<(), serde_json::Error> {
     assert_eq!(result, user);
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "-a", API_KEY, "user", "remove", user_name]);
+    cmd.args(["-j", "-u", URL, "-a", API_KEY, "user", "get", user_name]);
+    let output = cmd.output().expect("listthedocs output");
+    let result: User = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
+    assert_ne!(result.api_keys[0].key, user.api_keys[0].key);
+    assert!(result.api_keys[0].key.ends_with("********"));
+
+    let mut cmd = process::Command::new(&exe);
+    cmd.args([
+        "-j",
+        "-u",
+        URL,
+        "-a",
+        API_KEY,
+        "--show-secrets",
+        "user",
+        "remove",
+        user_name,
+    ]);
     let output = cmd.output().expect("listthedocs output");
-    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), user_name);
+    let result: Outcome<User> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
+    assert_eq!(result.change, "removed");
+    assert_eq!(result.previous, Some(user));
 
     Ok(())
 }
@@ -258,7 +390,7 @@ fn add_get_remove_roles() -> Result<(), serde_json::Error> {
     let exe = find_exe();
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -270,11 +402,11 @@ fn add_get_remove_roles() -> Result<(), serde_json::Error> {
     ]);
     let _ = cmd.output().expect("listthedocs output");
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "-a", API_KEY, "user", "remove", user_name]);
+    cmd.args(["-j", "-u", URL, "-a", API_KEY, "user", "remove", user_name]);
     let _ = cmd.output().expect("listthedocs output");
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -288,7 +420,7 @@ fn add_get_remove_roles() -> Result<(), serde_json::Error> {
     let _ = cmd.output().expect("listthedocs output");
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j", "-u", URL, "-a", API_KEY, "user", "add", user_name, "false",
     ]);
     let _ = cmd.output().expect("listthedocs output");
@@ -307,7 +439,7 @@ fn add_get_remove_roles() -> Result<(), serde_json::Error> {
     ];
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -321,13 +453,13 @@ fn add_get_remove_roles() -> Result<(), serde_json::Error> {
     ]);
     let _ = cmd.output().expect("listthedocs output");
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "-a", API_KEY, "role", "get", user_name]);
+    cmd.args(["-j", "-u", URL, "-a", API_KEY, "role", "get", user_name]);
     let output = cmd.output().expect("listthedocs output");
     let result: Vec<Role> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
     assert_eq!(result, roles);
 
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&[
+    cmd.args([
         "-j",
         "-u",
         URL,
@@ -340,7 +472,7 @@ fn add_get_remove_roles() -> Result<(), serde_json::Error> {
     ]);
     let _ = cmd.output().expect("listthedocs output");
     let mut cmd = process::Command::new(&exe);
-    cmd.args(&["-j", "-u", URL, "-a", API_KEY, "role", "get", user_name]);
+    cmd.args(["-j", "-u", URL, "-a", API_KEY, "role", "get", user_name]);
     let output = cmd.output().expect("listthedocs output");
     let result: Vec<Role> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))?;
     assert_eq!(result[0], roles[1]);
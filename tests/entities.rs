@@ -36,3 +36,13 @@ pub struct User {
     pub api_keys: Vec<ApiKey>,
     pub roles: Vec<Role>,
 }
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Outcome<T> {
+    pub entity: String,
+    pub id: String,
+    pub change: String,
+    pub no_op: bool,
+    pub previous: Option<T>,
+    pub current: Option<T>,
+}